@@ -0,0 +1,245 @@
+//! The `#[derive(FromIni)]` macro of dotini, enabled with its `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, GenericArgument, PathArguments};
+use syn::{Error, Expr, ExprLit, Lit, LitStr, Token, Type};
+
+/**
+ * Implements `dotini::FromIni` for a struct with named fields, reading each field from the
+ * property of the same name with `FromStr`. An `Option` field is `None` when its property is
+ * missing, and a field marked `#[ini(section)]` is read from the section of the same name,
+ * which for the top-level struct read by `from_ini` maps sections to nested structs.
+ *
+ * Fields also take these attributes:
+ * * `#[ini(rename = "max-connections")]` reads the property or section of that name instead of
+ *   the name of the field, for names that are not Rust identifiers.
+ * * `#[ini(alias = "max_conn")]` reads another name when the main one is missing, e.g. a legacy
+ *   key. It can be repeated, and the aliases are tried in order.
+ * * `#[ini(default = 10)]` uses a value when every name is missing; a string literal converts
+ *   into the type of the field with `From`, and a bare `#[ini(default)]` uses `Default`.
+ *
+ * ```rust
+ * use dotini::{FromIni, INIParser};
+ *
+ * #[derive(FromIni)]
+ * struct AppConfig {
+ *     name: String,
+ *     #[ini(section)]
+ *     server: Server,
+ *     #[ini(section)]
+ *     db: Option<Db>,
+ * }
+ *
+ * #[derive(FromIni)]
+ * struct Server {
+ *     host: String,
+ *     port: u16,
+ *     workers: Option<u8>,
+ * }
+ *
+ * #[derive(FromIni)]
+ * struct Db {
+ *     url: String,
+ * }
+ *
+ * let parser = INIParser::from_string("name = demo\n[server]\nhost = localhost\nport = 8080\n").unwrap();
+ * let cfg = AppConfig::from_ini(&parser).unwrap();
+ * assert_eq!(cfg.name, "demo");
+ * assert_eq!((cfg.server.host.as_str(), cfg.server.port, cfg.server.workers), ("localhost", 8080, None));
+ * assert!(cfg.db.is_none());
+ *
+ * let parser = INIParser::from_string("name = demo\n[server]\nhost = localhost\nport = http\n").unwrap();
+ * let err = AppConfig::from_ini(&parser).err().unwrap();
+ * assert!(err.to_string().starts_with("server.port: expected u16, got 'http'"));
+ * ```
+ *
+ * ```rust
+ * use dotini::{FromIni, INIParser};
+ *
+ * #[derive(FromIni)]
+ * struct Pool {
+ *     #[ini(rename = "max-connections", alias = "max_conn", default = 10)]
+ *     max_connections: u32,
+ *     #[ini(default = "localhost")]
+ *     host: String,
+ *     #[ini(default)]
+ *     verbose: bool,
+ * }
+ *
+ * let parser = INIParser::from_string("[pool]\nmax_conn = 50\n").unwrap();
+ * let pool = Pool::from_section(&parser, "pool").unwrap();
+ * assert_eq!((pool.max_connections, pool.host.as_str(), pool.verbose), (50, "localhost", false));
+ *
+ * let parser = INIParser::from_string("[pool]\nmax-connections = 80\nmax_conn = 50\n").unwrap();
+ * assert_eq!(Pool::from_section(&parser, "pool").unwrap().max_connections, 80);
+ * assert_eq!(Pool::from_section(&parser, "missing").unwrap().max_connections, 10);
+ * ```
+ */
+#[proc_macro_derive(FromIni, attributes(ini))]
+pub fn derive_from_ini(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "FromIni can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "FromIni can only be derived for structs",
+            ))
+        }
+    };
+    let reads = fields
+        .iter()
+        .map(read_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::dotini::FromIni for #name #type_generics #where_clause {
+            fn from_section(
+                parser: &::dotini::INIParser,
+                section: &str,
+            ) -> ::dotini::INIParserResult<Self> {
+                Ok(Self { #(#reads,)* })
+            }
+        }
+    })
+}
+
+/// The `#[ini(...)]` attributes of a field.
+#[derive(Default)]
+struct FieldOptions {
+    /// Read the field from the section of its name rather than from a property.
+    section: bool,
+    /// The name to read instead of the name of the field.
+    rename: Option<LitStr>,
+    /// Other names to read when the main one is missing, in order.
+    aliases: Vec<LitStr>,
+    /// The value to use when every name is missing.
+    default: Option<TokenStream2>,
+}
+
+impl FieldOptions {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("ini"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("section") {
+                    options.section = true;
+                } else if meta.path.is_ident("rename") {
+                    options.rename = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("alias") {
+                    options.aliases.push(meta.value()?.parse()?);
+                } else if meta.path.is_ident("default") {
+                    options.default = Some(match meta.input.peek(Token![=]) {
+                        // A string literal also initializes owned types like `String`.
+                        true => match meta.value()?.parse()? {
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Str(lit), ..
+                            }) => quote! { ::core::convert::From::from(#lit) },
+                            expr => quote! { #expr },
+                        },
+                        false => quote! { ::core::default::Default::default() },
+                    });
+                } else {
+                    return Err(meta.error("unknown `ini` attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        if options.default.is_some() && option_inner(&field.ty).is_some() {
+            return Err(Error::new_spanned(
+                &field.ty,
+                "`default` cannot be used on `Option` fields, which are `None` when missing",
+            ));
+        }
+        Ok(options)
+    }
+}
+
+/// Returns the `field: value` initializer reading a field.
+fn read_field(field: &Field) -> syn::Result<TokenStream2> {
+    let options = FieldOptions::parse(field)?;
+    let ident = field.ident.as_ref().expect("named fields have names");
+    let key = match &options.rename {
+        Some(rename) => rename.value(),
+        None => ident.unraw().to_string(),
+    };
+    let exists = match options.section {
+        true => quote! { parser.contains_section(name) },
+        false => quote! { parser.contains_key(section, name) },
+    };
+    let aliases = &options.aliases;
+    let name = match aliases.is_empty() {
+        true => quote! { #key },
+        false => quote! {{
+            let names: &[&str] = &[#key, #(#aliases),*];
+            names.iter().copied().find(|name| #exists).unwrap_or(#key)
+        }},
+    };
+    let ty = &field.ty;
+    let read = match (options.section, option_inner(ty), &options.default) {
+        (false, None, None) => quote! { parser.get_as(section, #name)? },
+        (false, None, Some(default)) => quote! {
+            match parser.get_opt(section, #name)? {
+                Some(value) => value,
+                None => #default,
+            }
+        },
+        (false, Some(_), _) => quote! { parser.get_opt(section, #name)? },
+        (true, None, None) => quote! { <#ty as ::dotini::FromIni>::from_section(parser, #name)? },
+        (true, None, Some(default)) => quote! {{
+            let name = #name;
+            match #exists {
+                true => <#ty as ::dotini::FromIni>::from_section(parser, name)?,
+                false => #default,
+            }
+        }},
+        (true, Some(ty), _) => quote! {{
+            let name = #name;
+            match #exists {
+                true => Some(<#ty as ::dotini::FromIni>::from_section(parser, name)?),
+                false => None,
+            }
+        }},
+    };
+    Ok(quote! { #ident: #read })
+}
+
+/// Returns `T` for a field written as `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
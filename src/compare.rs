@@ -0,0 +1,98 @@
+use crate::{section, INIParser, Properties};
+
+/// Options controlling how `INIParser::semantic_eq` compares two configs.
+#[derive(Debug, Clone, Copy)]
+pub struct EqOptions {
+    pub(crate) ignore_key_case: bool,
+    pub(crate) ignore_order: bool,
+}
+
+impl Default for EqOptions {
+    fn default() -> Self {
+        Self {
+            ignore_key_case: false,
+            ignore_order: true,
+        }
+    }
+}
+
+impl EqOptions {
+    /// Creates the default options: keys are case-sensitive and ordering is ignored.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treats keys that only differ in case as the same key, folding case like
+    /// `ParserOptions::case_insensitive`.
+    pub fn ignore_key_case(mut self, ignore: bool) -> Self {
+        self.ignore_key_case = ignore;
+        self
+    }
+
+    /// Ignores the order in which sections and keys are stored.
+    pub fn ignore_order(mut self, ignore: bool) -> Self {
+        self.ignore_order = ignore;
+        self
+    }
+}
+
+impl INIParser {
+    /**
+     * Checks whether two configs hold the same data.
+     *
+     * # Arguments
+     * * `other` - The config to compare against.
+     * * `options` - An `EqOptions` describing which differences to ignore.
+     *
+     * # Returns
+     * Returns `true` if both configs have the same sections, keys and values under the given
+     * options.
+     *
+     * ```rust
+     * use dotini::{EqOptions, INIParser};
+     *
+     * let a = INIParser::from_string("[db]\nHost=localhost\n").unwrap();
+     * let b = INIParser::from_string("[db]\nhost=localhost\n").unwrap();
+     * assert!(a != b);
+     * assert!(a.semantic_eq(&b, EqOptions::new().ignore_key_case(true)));
+     * ```
+     */
+    pub fn semantic_eq(&self, other: &INIParser, options: EqOptions) -> bool {
+        if self.output.len() != other.output.len() {
+            return false;
+        }
+        if !options.ignore_order && !self.output.keys().eq(other.output.keys()) {
+            return false;
+        }
+        self.output.iter().all(|(section, properties)| {
+            other.output.get(section).is_some_and(|other_properties| {
+                properties_eq(properties, other_properties, options)
+            })
+        })
+    }
+}
+
+/// Compares the properties of two sections under the given options.
+fn properties_eq(left: &Properties, right: &Properties, options: EqOptions) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+    if !options.ignore_key_case {
+        return (options.ignore_order || left.keys().eq(right.keys())) && left == right;
+    }
+    if !options.ignore_order
+        && !left
+            .keys()
+            .zip(right.keys())
+            .all(|(a, b)| section::same_name(a, b, true))
+    {
+        return false;
+    }
+    let fold = |properties: &Properties| -> Properties {
+        properties
+            .iter()
+            .map(|(key, value)| (section::fold_case(key).collect(), value.clone()))
+            .collect()
+    };
+    fold(left) == fold(right)
+}
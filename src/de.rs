@@ -0,0 +1,457 @@
+use crate::{parse_bool, parse_float, parse_integer};
+use crate::{INIParser, INIParserResult, InIParseError, Section, ValueError};
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt::Display;
+
+/**
+ * Parses INI text straight into a type implementing `serde::Deserialize`.
+ *
+ * The sections map to the fields of the top-level struct, each section being itself a struct
+ * or map of its properties, while the properties of the default section map to plain fields
+ * of the top-level struct. Values are read as the type of their field: booleans, integers and
+ * floats as `INIParser::get_bool`, `INIParser::get_int` and `INIParser::get_float` read them,
+ * sequences as comma-separated lists, and enums by the name of a unit variant. An empty value
+ * reads as `None` for an `Option`, unless it is written in quotes as `""`.
+ *
+ * A parsed config can be deserialized as well, since `&INIParser` implements
+ * `serde::Deserializer`: `AppConfig::deserialize(&parser)`.
+ *
+ * # Arguments
+ * * `content` - An INI-formatted string to parse.
+ *
+ * # Returns
+ * Returns the deserialized value, the error of a value that cannot be read as the type of its
+ * field, like `InIParseError::InvalidNumber`, or `InIParseError::DeserializationError` for
+ * other mismatches between the config and the type, like a missing field.
+ *
+ * ```rust
+ * use serde::Deserialize;
+ *
+ * #[derive(Debug, Deserialize)]
+ * struct AppConfig {
+ *     name: String,
+ *     server: Server,
+ *     db: Option<Db>,
+ * }
+ *
+ * #[derive(Debug, Deserialize)]
+ * struct Server {
+ *     host: String,
+ *     port: u16,
+ *     tls: bool,
+ *     aliases: Vec<String>,
+ *     timeout: Option<f64>,
+ * }
+ *
+ * #[derive(Debug, Deserialize)]
+ * struct Db {
+ *     url: String,
+ * }
+ *
+ * let content = "name = demo\n[server]\nhost = localhost\nport = 8080\ntls = yes\naliases = www, api\ntimeout =\n";
+ * let cfg: AppConfig = dotini::from_str(content).unwrap();
+ * assert_eq!(cfg.name, "demo");
+ * assert_eq!((cfg.server.host.as_str(), cfg.server.port, cfg.server.tls), ("localhost", 8080, true));
+ * assert_eq!(cfg.server.aliases, ["www", "api"]);
+ * assert_eq!(cfg.server.timeout, None);
+ * assert!(cfg.db.is_none());
+ *
+ * let err = dotini::from_str::<AppConfig>("name = demo\n[server]\nport = abc\n").unwrap_err();
+ * assert_eq!(err.to_string(), "server.port: expected integer, got 'abc' (invalid digit found in string)");
+ * let err = dotini::from_str::<AppConfig>("name = demo\n[server]\nport = 65536\n").unwrap_err();
+ * assert_eq!(err.to_string(), "server.port: expected integer, got '65536' (number too large to fit in target type)");
+ * ```
+ *
+ * Integers use the full range of their type, up to `i128` and `u128`:
+ *
+ * ```rust
+ * use serde::Deserialize;
+ *
+ * #[derive(Deserialize)]
+ * struct Limits {
+ *     max: u64,
+ *     min: i128,
+ *     mask: u128,
+ * }
+ *
+ * let content = "max = 18446744073709551615\nmin = -170141183460469231731687303715884105728\nmask = 0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff\n";
+ * let limits: Limits = dotini::from_str(content).unwrap();
+ * assert_eq!((limits.max, limits.min, limits.mask), (u64::MAX, i128::MIN, u128::MAX));
+ * ```
+ *
+ * Keys that are not Rust identifiers, legacy names and defaults are mapped with serde's own
+ * field attributes:
+ *
+ * ```rust
+ * use serde::Deserialize;
+ *
+ * #[derive(Deserialize)]
+ * struct Config {
+ *     pool: Pool,
+ * }
+ *
+ * #[derive(Deserialize)]
+ * struct Pool {
+ *     #[serde(rename = "max-connections", alias = "max_conn", default = "default_connections")]
+ *     max_connections: u32,
+ * }
+ *
+ * fn default_connections() -> u32 {
+ *     10
+ * }
+ *
+ * let cfg: Config = dotini::from_str("[pool]\nmax_conn = 50\n").unwrap();
+ * assert_eq!(cfg.pool.max_connections, 50);
+ * let cfg: Config = dotini::from_str("[pool]\n").unwrap();
+ * assert_eq!(cfg.pool.max_connections, 10);
+ * ```
+ */
+pub fn from_str<T: DeserializeOwned>(content: &str) -> INIParserResult<T> {
+    let parser = INIParser::from_string(content)?;
+    T::deserialize(&parser)
+}
+
+impl de::Error for InIParseError {
+    fn custom<T: Display>(message: T) -> Self {
+        Self::DeserializationError(message.to_string())
+    }
+}
+
+/// Deserializes a config as a map of its sections, preceded by the properties of the default
+/// section.
+impl<'de> de::Deserializer<'de> for &'de INIParser {
+    type Error = InIParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        let globals = self.globals();
+        let mut entries: Vec<_> = globals
+            .iter()
+            .flat_map(|section| section.iter())
+            .map(|(key, value)| {
+                let section = &self.options.default_section;
+                (
+                    key,
+                    Item::Value(ValueDeserializer::new(self, section, key, value)),
+                )
+            })
+            .collect();
+        for name in self.sections() {
+            if globals.is_some_and(|globals| globals.name() == name) {
+                continue;
+            }
+            let section = self.section(name).ok_or(InIParseError::Unreachable)?;
+            let section = SectionDeserializer {
+                parser: self,
+                section,
+            };
+            entries.push((section.section.name(), Item::Section(section)));
+        }
+        visitor.visit_map(Entries {
+            entries: entries.into_iter(),
+            pending: None,
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> INIParserResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// A section or a property of the default section, as the value of a top-level field.
+enum Item<'a> {
+    Value(ValueDeserializer<'a>),
+    Section(SectionDeserializer<'a>),
+}
+
+/// The fields of a config or of a section, in order.
+struct Entries<'a, I> {
+    entries: I,
+    pending: Option<Item<'a>>,
+}
+
+impl<'de, I> MapAccess<'de> for Entries<'de, I>
+where
+    I: Iterator<Item = (&'de str, Item<'de>)>,
+{
+    type Error = InIParseError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> INIParserResult<Option<K::Value>> {
+        let Some((key, item)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.pending = Some(item);
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> INIParserResult<V::Value> {
+        match self.pending.take().ok_or(InIParseError::Unreachable)? {
+            Item::Value(value) => seed.deserialize(value),
+            Item::Section(section) => seed.deserialize(section),
+        }
+    }
+}
+
+/// Deserializes a section as a map of its properties.
+struct SectionDeserializer<'a> {
+    parser: &'a INIParser,
+    section: Section<'a>,
+}
+
+impl<'de> de::Deserializer<'de> for SectionDeserializer<'de> {
+    type Error = InIParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        let (parser, section) = (self.parser, self.section.name());
+        let entries = self.section.iter().map(move |(key, value)| {
+            let value = ValueDeserializer::new(parser, section, key, value);
+            (key, Item::Value(value))
+        });
+        visitor.visit_map(Entries {
+            entries,
+            pending: None,
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> INIParserResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Deserializes the value of a property as the type asked for, naming the property in errors.
+#[derive(Clone, Copy)]
+struct ValueDeserializer<'a> {
+    section: &'a str,
+    key: &'a str,
+    value: &'a str,
+    /// The value was written in quotes, so that an empty one is not `None`.
+    quoted: bool,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn new(parser: &INIParser, section: &'a str, key: &'a str, value: &'a str) -> Self {
+        let quoted = parser
+            .value_span(section, key)
+            .is_some_and(|span| !span.range.is_empty());
+        Self {
+            section,
+            key,
+            value,
+            quoted,
+        }
+    }
+
+    fn error(&self, expected: &str, reason: Option<String>) -> ValueError {
+        ValueError::new(self.section, self.key, self.value, expected, reason)
+    }
+
+    fn integer<T: TryFrom<i128> + TryFrom<u128>>(&self) -> INIParserResult<T> {
+        parse_integer(self.value)
+            .map_err(|reason| InIParseError::InvalidNumber(self.error("integer", Some(reason))))
+    }
+
+    fn float(&self) -> INIParserResult<f64> {
+        parse_float(self.value)
+            .map_err(|reason| InIParseError::InvalidNumber(self.error("number", Some(reason))))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = InIParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        match parse_bool(self.value) {
+            Some(value) => visitor.visit_bool(value),
+            None => Err(InIParseError::InvalidBool(self.error("boolean", None))),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_i8(self.integer()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_i16(self.integer()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_i32(self.integer()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_i64(self.integer()?)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_i128(self.integer()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_u8(self.integer()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_u16(self.integer()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_u32(self.integer()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_u64(self.integer()?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_u128(self.integer()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_f32(self.float()? as f32)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_f64(self.float()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        let mut chars = self.value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(InIParseError::ConversionError(self.error("char", None))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        match self.value.is_empty() && !self.quoted {
+            true => visitor.visit_none(),
+            false => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> INIParserResult<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> INIParserResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        let items = self
+            .value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty());
+        visitor.visit_seq(Items {
+            property: self,
+            items,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> INIParserResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> INIParserResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> INIParserResult<V::Value> {
+        visitor.visit_enum(BorrowedStrDeserializer::new(self.value))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> INIParserResult<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf map struct identifier
+    }
+}
+
+/// The items of a comma-separated value.
+struct Items<'a, I> {
+    property: ValueDeserializer<'a>,
+    items: I,
+}
+
+impl<'de, I: Iterator<Item = &'de str>> SeqAccess<'de> for Items<'de, I> {
+    type Error = InIParseError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> INIParserResult<Option<T::Value>> {
+        let Some(value) = self.items.next() else {
+            return Ok(None);
+        };
+        let item = ValueDeserializer {
+            value,
+            quoted: false,
+            ..self.property
+        };
+        seed.deserialize(item).map(Some)
+    }
+}
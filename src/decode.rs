@@ -0,0 +1,33 @@
+use crate::{INIParserResult, InIParseError};
+
+/// Decodes the content of a file as UTF-8 or UTF-16. A byte order mark selects the encoding
+/// and is removed; without one, text starting with an ASCII character next to a zero byte is
+/// taken as UTF-16 of the corresponding byte order, as written by many Windows tools, and
+/// anything else as UTF-8.
+pub(crate) fn decode(bytes: Vec<u8>) -> INIParserResult<String> {
+    match bytes.as_slice() {
+        [0xef, 0xbb, 0xbf, rest @ ..] => utf8(rest.to_vec()),
+        [0xff, 0xfe, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [first, 0, ..] if first.is_ascii() && *first != 0 => utf16(&bytes, u16::from_le_bytes),
+        [0, second, ..] if second.is_ascii() && *second != 0 => utf16(&bytes, u16::from_be_bytes),
+        _ => utf8(bytes),
+    }
+}
+
+fn utf8(bytes: Vec<u8>) -> INIParserResult<String> {
+    String::from_utf8(bytes).map_err(|err| InIParseError::FileReadError(err.to_string()))
+}
+
+fn utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> INIParserResult<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(InIParseError::FileReadError(
+            "invalid UTF-16: odd number of bytes".to_string(),
+        ));
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| unit([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16(&units).map_err(|err| InIParseError::FileReadError(err.to_string()))
+}
@@ -0,0 +1,146 @@
+use crate::{InIParseError, ParseError, Warning};
+use miette::{
+    Diagnostic, LabeledSpan, MietteError, MietteSpanContents, Severity, SourceCode, SourceSpan,
+    SpanContents,
+};
+
+/**
+ * Renders parse errors as annotated snippets pointing at the offending character.
+ *
+ * ```rust
+ * use dotini::{INIParser, InIParseError};
+ * use miette::{GraphicalReportHandler, GraphicalTheme};
+ *
+ * let err = INIParser::from_string("[server]\nhost = localhost\n[broken\n").unwrap_err();
+ * let InIParseError::UnsuccessfulParse(err) = err else { panic!() };
+ * let mut report = String::new();
+ * GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+ *     .render_report(&mut report, &err)
+ *     .unwrap();
+ * assert!(report.contains("3 │ [broken"));
+ * assert!(report.contains("E0001"));
+ * assert!(report.contains("expected subsection, parent, or conditions"));
+ * ```
+ */
+impl Diagnostic for ParseError {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(self)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(label(&self.source_line, self.column, &self.message))
+    }
+}
+
+impl SourceCode for ParseError {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        _context_lines_before: usize,
+        _context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        Ok(contents(
+            &self.source_line,
+            self.line,
+            self.file.as_deref(),
+            span,
+        ))
+    }
+}
+
+impl Diagnostic for Warning {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(Severity::Warning)
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(self)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(label(&self.source_line, self.column, &self.message))
+    }
+}
+
+impl SourceCode for Warning {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        _context_lines_before: usize,
+        _context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        Ok(contents(
+            &self.source_line,
+            self.line,
+            self.file.as_deref(),
+            span,
+        ))
+    }
+}
+
+/// The message of an `InIParseError::UnsuccessfulParse` already quotes the offending line, so
+/// render its `ParseError` for an annotated snippet. `InIParseError::Multiple` renders each of
+/// the errors it holds that way.
+impl Diagnostic for InIParseError {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        let Self::Multiple(errors) = self else {
+            return None;
+        };
+        Some(Box::new(errors.iter().map(|err| match err {
+            Self::UnsuccessfulParse(err) => err as &dyn Diagnostic,
+            err => err,
+        })))
+    }
+}
+
+/// Labels the character at `column` of a line, or its last one if the column lies past it, as
+/// for a missing closing bracket.
+fn label<'a>(
+    source_line: &str,
+    column: usize,
+    message: &str,
+) -> Box<dyn Iterator<Item = LabeledSpan> + 'a> {
+    let (offset, len) = source_line
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .or_else(|| source_line.char_indices().last())
+        .map_or((0, 0), |(offset, c)| (offset, c.len_utf8()));
+    let span = LabeledSpan::new_primary_with_span(Some(message.to_string()), (offset, len));
+    Box::new(std::iter::once(span))
+}
+
+/// Returns a line as the whole source code of a diagnostic, numbered `line` as in the parsed
+/// text, with the column of the requested `span`.
+fn contents<'a>(
+    source_line: &'a str,
+    line: usize,
+    file: Option<&std::path::Path>,
+    span: &SourceSpan,
+) -> Box<dyn SpanContents<'a> + 'a> {
+    let data = source_line.as_bytes();
+    let column = source_line
+        .get(..span.offset())
+        .map_or(0, |before| before.chars().count());
+    let (span, line) = (
+        SourceSpan::from((0, source_line.len())),
+        line.saturating_sub(1),
+    );
+    Box::new(match file {
+        Some(file) => {
+            MietteSpanContents::new_named(file.display().to_string(), data, span, line, column, 1)
+        }
+        None => MietteSpanContents::new(data, span, line, column, 1),
+    })
+}
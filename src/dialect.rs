@@ -0,0 +1,97 @@
+use crate::{Dialect, ParserOptions};
+use std::fmt;
+use std::ops::Range;
+use std::panic::RefUnwindSafe;
+
+/// Hooks for INI variants the built-in options do not cover, installed with
+/// `ParserOptions::custom_dialect`. Every method has a default that leaves the built-in
+/// behavior unchanged, so a dialect only overrides what sets it apart.
+///
+/// ```rust
+/// use dotini::{DuplicateKeyPolicy, INIParser, IniDialect, ParserOptions};
+/// use std::ops::Range;
+///
+/// /// Reads `key => value` lines with `//` comments and case-folded keys.
+/// #[derive(Debug)]
+/// struct Arrows;
+///
+/// impl IniDialect for Arrows {
+///     fn options(&self) -> ParserOptions {
+///         ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::Error)
+///     }
+///
+///     fn is_comment(&self, line: &str) -> bool {
+///         line.trim_start().starts_with("//")
+///     }
+///
+///     fn delimiter(&self, line: &str) -> Option<Range<usize>> {
+///         line.find("=>").map(|at| at..at + 2)
+///     }
+///
+///     fn normalize_key(&self, key: String) -> String {
+///         key.to_lowercase()
+///     }
+///
+///     fn unescape(&self, value: String) -> String {
+///         value.replace("%20", " ")
+///     }
+/// }
+///
+/// let content = "// routes\n[routes]\nHome => /index%20page\nabout => /about\n";
+/// let options = ParserOptions::custom_dialect(Arrows);
+/// let parser = INIParser::from_string_with_options(content, options.clone()).unwrap();
+/// assert_eq!(parser.get("routes", "home"), Some("/index page"));
+/// assert_eq!(parser.get("routes", "about"), Some("/about"));
+/// assert!(INIParser::from_string_with_options("[a]\nx => 1\nX => 2\n", options).is_err());
+/// ```
+///
+/// Dialects are `Send`, `Sync` and `RefUnwindSafe`, so options and parsers holding one can
+/// be shared between threads and used across `catch_unwind`:
+///
+/// ```rust
+/// use dotini::{INIParser, ParserOptions};
+/// use std::panic::{RefUnwindSafe, UnwindSafe};
+///
+/// fn assert_shareable<T: Send + Sync + UnwindSafe + RefUnwindSafe>() {}
+/// assert_shareable::<ParserOptions>();
+/// assert_shareable::<INIParser>();
+/// ```
+pub trait IniDialect: fmt::Debug + Send + Sync + RefUnwindSafe {
+    /// Returns the options the dialect builds on, such as its comment prefixes, delimiters,
+    /// quoting rules and duplicate policies.
+    fn options(&self) -> ParserOptions {
+        ParserOptions::new()
+    }
+
+    /// Returns `true` for lines that are comments in addition to those starting with one of
+    /// the comment prefixes.
+    fn is_comment(&self, line: &str) -> bool {
+        let _ = line;
+        false
+    }
+
+    /// Splits a line into a property before the built-in grammar sees it. Returns the byte
+    /// range of the delimiter within the line, with the key and value being the text around it
+    /// without surrounding whitespace, or `None` to parse the line as usual.
+    fn delimiter(&self, line: &str) -> Option<Range<usize>> {
+        let _ = line;
+        None
+    }
+
+    /// Normalizes a key once it is read, e.g. by folding its case.
+    fn normalize_key(&self, key: String) -> String {
+        key
+    }
+
+    /// Resolves the dialect's escape sequences in a value once quotes and built-in escapes are
+    /// handled.
+    fn unescape(&self, value: String) -> String {
+        value
+    }
+}
+
+impl IniDialect for Dialect {
+    fn options(&self) -> ParserOptions {
+        ParserOptions::dialect(*self)
+    }
+}
@@ -0,0 +1,501 @@
+use crate::decode;
+use crate::lexer::{self, Token};
+use crate::writer::{header, quote_key, validate_property, validate_section};
+use crate::{
+    section, INIParser, INIParserResult, InIParseError, ParserOptions, QuotePolicy, WriteOptions,
+    DEFAULT_SECTION,
+};
+use std::fmt::{self, Display};
+use std::fs;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// A single line of an `IniDocument`, kept exactly as it was read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Line {
+    text: String,
+    eol: String,
+    kind: LineKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LineKind {
+    /// A blank or comment line.
+    Other,
+    Section(String),
+    Property {
+        section: String,
+        key: String,
+        key_span: Range<usize>,
+        value_span: Range<usize>,
+        value: String,
+    },
+}
+
+/// A lossless representation of an INI file.
+///
+/// Unlike `INIParser`, which only keeps sections and properties, an `IniDocument` keeps every
+/// line of the input together with its comments, whitespace and line endings. Edits only touch
+/// the lines they change, so a user-maintained file can be updated without losing its
+/// formatting.
+///
+/// ```rust
+/// use dotini::IniDocument;
+///
+/// let mut doc: IniDocument = "; tuned by hand\n[server]\n  port   =   80\n\n[db]\n".parse().unwrap();
+/// doc.set("server", "port", "8080").unwrap();
+/// doc.set("server", "host", "localhost").unwrap();
+/// doc.set("cache", "ttl", "60").unwrap();
+/// assert_eq!(
+///     doc.to_string(),
+///     "; tuned by hand\n[server]\n  port   =   8080\n  host   =   localhost\n\n[db]\n\n[cache]\nttl = 60\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniDocument {
+    lines: Vec<Line>,
+    /// The section of the properties before the first header, see
+    /// `ParserOptions::default_section`.
+    default_section: String,
+    /// Set when names only differing in case are the same, see
+    /// `ParserOptions::case_insensitive`.
+    case_insensitive: bool,
+}
+
+impl Default for IniDocument {
+    fn default() -> Self {
+        Self {
+            lines: Vec::new(),
+            default_section: DEFAULT_SECTION.to_string(),
+            case_insensitive: false,
+        }
+    }
+}
+
+impl IniDocument {
+    /// Creates an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Parses an INI-formatted string into a document.
+     *
+     * # Arguments
+     * * `content` - An INI-formatted string to parse.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the `IniDocument`, or an `INIParseError` if there
+     * is an issue parsing the content.
+     */
+    pub fn parse(content: &str) -> INIParserResult<Self> {
+        Self::parse_with_options(content, &ParserOptions::default())
+    }
+
+    /**
+     * Parses an INI-formatted string into a document using the given options.
+     * Options that only affect the resulting config, such as `ParserOptions::order`, are
+     * ignored since a document keeps its lines as they are. The document keeps the
+     * `ParserOptions::default_section` and `ParserOptions::case_insensitive` settings to look
+     * up and edit names with.
+     *
+     * ```rust
+     * use dotini::{IniDocument, ParserOptions};
+     *
+     * let options = ParserOptions::new().default_section("general").case_insensitive(true);
+     * let content = "name = demo\n[Server]\nPort = 80\n";
+     * let mut doc = IniDocument::parse_with_options(content, &options).unwrap();
+     * assert_eq!(doc.get("general", "name"), Some("demo"));
+     * assert_eq!(doc.set("SERVER", "port", "8080").unwrap(), Some("80".to_string()));
+     * doc.set("General", "debug", "true").unwrap();
+     * assert_eq!(doc.to_string(), "name = demo\ndebug = true\n[Server]\nPort = 8080\n");
+     * assert_eq!(doc.to_parser().get("server", "PORT"), Some("8080"));
+     * ```
+     *
+     * # Arguments
+     * * `content` - An INI-formatted string to parse.
+     * * `options` - A `ParserOptions` describing the syntax of the content.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the `IniDocument`, or an `INIParseError` if there
+     * is an issue parsing the content.
+     */
+    pub fn parse_with_options(content: &str, options: &ParserOptions) -> INIParserResult<Self> {
+        lexer::check_size(content, options)?;
+        let mut lines = Vec::new();
+        let mut current_section = options.default_section.clone();
+        for raw in lexer::lines(content, options) {
+            let token = match lexer::tokenize(content, &raw, options) {
+                Err(err) if options.lenient && !err.is_limit() => Token::Comment,
+                token => token?,
+            };
+            let kind = match token {
+                Token::Blank | Token::Comment | Token::Include { .. } => LineKind::Other,
+                Token::Section { name, .. } => {
+                    current_section = name;
+                    LineKind::Section(current_section.clone())
+                }
+                Token::Property {
+                    key_span,
+                    key,
+                    value_span,
+                    value,
+                } => LineKind::Property {
+                    section: current_section.clone(),
+                    key,
+                    key_span,
+                    value_span,
+                    value,
+                },
+            };
+            lines.push(Line {
+                text: raw.text.to_string(),
+                eol: raw.eol.to_string(),
+                kind,
+            });
+        }
+        Ok(Self {
+            lines,
+            default_section: options.default_section.clone(),
+            case_insensitive: options.case_insensitive,
+        })
+    }
+
+    /**
+     * Reads and parses an INI file into a document.
+     *
+     * # Arguments
+     * * `path` - A string containing the path to the INI file to parse.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the `IniDocument`, or an `INIParseError` if there
+     * is an issue reading or parsing the file.
+     */
+    pub fn from_file(path: &str) -> INIParserResult<Self> {
+        let bytes = fs::read(path).map_err(|err| InIParseError::FileReadError(err.to_string()))?;
+        let content = decode::decode(bytes)?;
+
+        Self::parse(&content)
+    }
+
+    /**
+     * Writes the document to a file, replacing any existing content.
+     *
+     * # Arguments
+     * * `path` - A string containing the path of the file to write.
+     *
+     * # Returns
+     * Returns `InIParseError::FileWriteError` if the file cannot be written.
+     */
+    pub fn write_to_file(&self, path: &str) -> INIParserResult<()> {
+        fs::write(path, self.to_string())
+            .map_err(|err| InIParseError::FileWriteError(err.to_string()))
+    }
+
+    /// Returns the names of the sections in the order they first appear.
+    pub fn sections(&self) -> Vec<&str> {
+        let mut sections: Vec<&str> = Vec::new();
+        for line in &self.lines {
+            if let LineKind::Section(name) = &line.kind {
+                if !sections.iter().any(|seen| self.same_name(seen, name)) {
+                    sections.push(name);
+                }
+            }
+        }
+        sections
+    }
+
+    /// Returns `true` if the document contains a section with the given name.
+    pub fn contains_section(&self, section: &str) -> bool {
+        self.lines.iter().any(
+            |line| matches!(&line.kind, LineKind::Section(name) if self.same_name(name, section)),
+        )
+    }
+
+    /**
+     * Returns the value of a property.
+     * If a key is repeated, the last occurrence wins, as with `INIParser`.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns `Some` with the property value, or `None` if it does not exist.
+     */
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        let line = &self.lines[self.find_property(section, key)?];
+        match &line.kind {
+            LineKind::Property { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /**
+     * Sets the value of a property.
+     *
+     * An existing property has only its value replaced, keeping the key, spacing, quotes and
+     * any trailing text of the line. A new property is added after the last property of its
+     * section, using the same layout as that property; a missing section is appended to the end
+     * of the document.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     * * `value` - The new value of the property.
+     *
+     * # Returns
+     * Returns the previous value of the property if there was one, or
+     * `InIParseError::SerializationError` if the section, key or value cannot be written.
+     */
+    pub fn set(
+        &mut self,
+        section: &str,
+        key: &str,
+        value: &str,
+    ) -> INIParserResult<Option<String>> {
+        validate_section(section, false)?;
+        // Values are quoted like the ones they replace rather than by a policy.
+        let options = WriteOptions::default().quote(QuotePolicy::Never);
+        validate_property(section, key, value, &options)?;
+
+        if let Some(index) = self.find_property(section, key) {
+            let line = &mut self.lines[index];
+            let LineKind::Property {
+                key_span,
+                value_span,
+                value: current,
+                ..
+            } = &mut line.kind
+            else {
+                return Err(InIParseError::Unreachable);
+            };
+            if value_span.start == key_span.end {
+                // A valueless key gets a delimiter before its first value.
+                line.text.insert_str(key_span.end, " = ");
+                *value_span = key_span.end + 3..key_span.end + 3;
+            }
+            let written = quote_like(&line.text[value_span.clone()], value);
+            line.text.replace_range(value_span.clone(), &written);
+            *value_span = value_span.start..value_span.start + written.len();
+            return Ok(Some(std::mem::replace(current, value.to_string())));
+        }
+
+        let template = self.last_property(section).map(|index| &self.lines[index]);
+        let (indent, separator) = match template {
+            Some(Line {
+                text,
+                kind:
+                    LineKind::Property {
+                        key_span,
+                        value_span,
+                        ..
+                    },
+                ..
+            }) => (
+                text[..key_span.start].to_string(),
+                text[key_span.end..value_span.start].to_string(),
+            ),
+            _ => (String::new(), " = ".to_string()),
+        };
+        let written = quote_like("", value);
+        let quoted = quote_key(key, false);
+        let text = format!("{indent}{quoted}{separator}{written}");
+        let key_span = indent.len()..indent.len() + quoted.len();
+        let value_start = key_span.end + separator.len();
+        let property = LineKind::Property {
+            section: section.to_string(),
+            key: key.to_string(),
+            key_span,
+            value_span: value_start..value_start + written.len(),
+            value: value.to_string(),
+        };
+
+        let index = match self.last_property(section).or_else(|| self.header(section)) {
+            Some(index) => index + 1,
+            None if self.same_name(section, &self.default_section) => 0,
+            None => {
+                if !self.lines.is_empty() {
+                    self.push(String::new(), LineKind::Other);
+                }
+                self.push(
+                    format!("[{}]", header(section, false)),
+                    LineKind::Section(section.to_string()),
+                );
+                self.lines.len()
+            }
+        };
+        self.insert(index, text, property);
+        Ok(None)
+    }
+
+    /**
+     * Removes a property, dropping every line that defines it.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property to remove.
+     *
+     * # Returns
+     * Returns the value that was in effect, or `None` if the property did not exist.
+     */
+    pub fn remove(&mut self, section: &str, key: &str) -> Option<String> {
+        let previous = self.get(section, key)?.to_string();
+        let ignore_case = self.case_insensitive;
+        self.lines
+            .retain(|line| !is_property(line, section, key, ignore_case));
+        Some(previous)
+    }
+
+    /**
+     * Removes a section with its header, properties and the comments inside it.
+     *
+     * # Arguments
+     * * `section` - The name of the section to remove.
+     *
+     * # Returns
+     * Returns `true` if the section existed and was removed.
+     */
+    pub fn remove_section(&mut self, section: &str) -> bool {
+        let before = self.lines.len();
+        let ignore_case = self.case_insensitive;
+        let mut inside = section::same_name(section, &self.default_section, ignore_case);
+        self.lines.retain(|line| {
+            if let LineKind::Section(name) = &line.kind {
+                inside = section::same_name(name, section, ignore_case);
+            }
+            !inside
+        });
+        self.lines.len() != before
+    }
+
+    /// Builds an `INIParser` holding the sections and properties of the document.
+    pub fn to_parser(&self) -> INIParser {
+        let mut parser = INIParser::from_map(Default::default());
+        parser.options = ParserOptions::new()
+            .default_section(&self.default_section)
+            .case_insensitive(self.case_insensitive);
+        for line in &self.lines {
+            match &line.kind {
+                LineKind::Other => (),
+                LineKind::Section(name) => {
+                    parser.entry(name).or_default();
+                }
+                LineKind::Property {
+                    section,
+                    key,
+                    value,
+                    ..
+                } => {
+                    parser.set(section, key.clone(), value);
+                }
+            }
+        }
+        parser
+    }
+
+    /// Returns the index of the line defining the effective value of a property.
+    fn find_property(&self, section: &str, key: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .rposition(|line| is_property(line, section, key, self.case_insensitive))
+    }
+
+    /// Returns the index of the last property line of a section.
+    fn last_property(&self, section: &str) -> Option<usize> {
+        self.lines.iter().rposition(|line| match &line.kind {
+            LineKind::Property { section: s, .. } => self.same_name(s, section),
+            _ => false,
+        })
+    }
+
+    /// Returns the index of the last header of a section.
+    fn header(&self, section: &str) -> Option<usize> {
+        self.lines.iter().rposition(
+            |line| matches!(&line.kind, LineKind::Section(name) if self.same_name(name, section)),
+        )
+    }
+
+    /// Returns `true` if two section names or keys name the same thing.
+    fn same_name(&self, a: &str, b: &str) -> bool {
+        section::same_name(a, b, self.case_insensitive)
+    }
+
+    /// The line terminator used for new lines, taken from the first terminated line.
+    fn newline(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.eol.as_str())
+            .find(|eol| !eol.is_empty())
+            .unwrap_or("\n")
+            .to_string()
+    }
+
+    fn push(&mut self, text: String, kind: LineKind) {
+        let index = self.lines.len();
+        self.insert(index, text, kind);
+    }
+
+    /// Inserts a line, terminating the line before it if needed.
+    fn insert(&mut self, index: usize, text: String, kind: LineKind) {
+        let newline = self.newline();
+        let mut eol = newline.clone();
+        if index == self.lines.len() {
+            if let Some(last) = self.lines.last_mut() {
+                if last.eol.is_empty() {
+                    // Keep the document ending without a line terminator.
+                    last.eol = newline;
+                    eol = String::new();
+                }
+            }
+        }
+        self.lines.insert(index, Line { text, eol, kind });
+    }
+}
+
+/// Returns `true` if a line defines the given property.
+fn is_property(line: &Line, section: &str, key: &str, ignore_case: bool) -> bool {
+    matches!(&line.kind, LineKind::Property { section: s, key: k, .. }
+        if section::same_name(s, section, ignore_case) && section::same_name(k, key, ignore_case))
+}
+
+/// Writes a value using the quotes of the value it replaces, quoting it with `"` if its
+/// surrounding whitespace would otherwise be lost.
+fn quote_like(previous: &str, value: &str) -> String {
+    let quote = match previous.chars().next() {
+        Some(quote @ ('"' | '\'')) if previous.len() > 1 && previous.ends_with(quote) => {
+            Some(quote)
+        }
+        _ if value.trim() != value => Some('"'),
+        _ => None,
+    };
+    match quote {
+        Some(quote) if !value.contains(quote) => format!("{quote}{value}{quote}"),
+        _ => value.to_string(),
+    }
+}
+
+/// Writes the document back exactly as it was read, plus any edits.
+impl Display for IniDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            f.write_str(&line.text)?;
+            f.write_str(&line.eol)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for IniDocument {
+    type Err = InIParseError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        Self::parse(content)
+    }
+}
+
+impl From<&IniDocument> for INIParser {
+    fn from(document: &IniDocument) -> Self {
+        document.to_parser()
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{lexer, INIParser, INIParserResult, Sections};
+use std::env;
+
+impl INIParser {
+    /**
+     * Parses the content of a `.env` file into the default section.
+     *
+     * Every line assigns a variable as `KEY=value`, optionally prefixed with `export`, and lines
+     * starting with `#` are comments. Unquoted values end at a ` #` comment, single-quoted
+     * values are taken literally, and double-quoted values may span several lines and resolve
+     * the `\n`, `\r`, `\t`, `\"`, `\\` and `\$` escapes. Outside single quotes, `$NAME` and
+     * `${NAME}` are replaced by the value of a variable defined earlier in the file, or else of
+     * the environment variable, or else by nothing.
+     *
+     * # Arguments
+     * * `content` - The text of the `.env` file.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the `INIParser`, or `InIParseError::UnsuccessfulParse`
+     * pointing at a line that is not an assignment, an invalid variable name or an unterminated
+     * quote or reference.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "# database\nexport DB_HOST=localhost\nDB_PORT=5432 # default\nDB_URL=\"postgres://${DB_HOST}:$DB_PORT/app\"\nGREETING='Hello, $USER'\nMOTD=\"line one\nline two\"\n";
+     * let parser = INIParser::from_dotenv(content).unwrap();
+     * let env = parser.globals().unwrap();
+     * assert_eq!(env.get("DB_PORT"), Some("5432"));
+     * assert_eq!(env.get("DB_URL"), Some("postgres://localhost:5432/app"));
+     * assert_eq!(env.get("GREETING"), Some("Hello, $USER"));
+     * assert_eq!(env.get("MOTD"), Some("line one\nline two"));
+     * assert!(INIParser::from_dotenv("NAME=\"unterminated\n").is_err());
+     * ```
+     */
+    pub fn from_dotenv(content: &str) -> INIParserResult<Self> {
+        let mut parser = Self::from_map(Sections::new());
+        let section = parser.options.default_section.clone();
+        let mut pos = 0;
+        while pos < content.len() {
+            let end = line_end(content, pos);
+            let line = content[pos..end].trim_start();
+            let start = end - line.len();
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                pos = end + 1;
+                continue;
+            }
+            let assignment = match line.strip_prefix("export") {
+                Some(rest) if rest.starts_with([' ', '\t']) => rest.trim_start(),
+                _ => line,
+            };
+            let assignment_start = start + line.len() - assignment.len();
+            let Some((key, _)) = assignment.split_once('=') else {
+                return Err(lexer::error_at(content, start, "expected `KEY=value`"));
+            };
+            let key = key.trim_end();
+            if key.is_empty() || !key.chars().all(is_name_char) {
+                return Err(lexer::error_at(
+                    content,
+                    assignment_start,
+                    "invalid variable name",
+                ));
+            }
+            let mut value_start = assignment_start + assignment.find('=').unwrap_or(0) + 1;
+            value_start += content[value_start..end].len()
+                - content[value_start..end]
+                    .trim_start_matches([' ', '\t'])
+                    .len();
+            let (value, value_end) = match content[value_start..].chars().next() {
+                Some('\'') => {
+                    let body = value_start + 1;
+                    let close = content[body..].find('\'').ok_or_else(|| {
+                        lexer::error_at(content, value_start, "unterminated single-quoted value")
+                    })? + body;
+                    (content[body..close].to_string(), close + 1)
+                }
+                Some('"') => {
+                    let body = value_start + 1;
+                    let close = closing_quote(&content[body..]).ok_or_else(|| {
+                        lexer::error_at(content, value_start, "unterminated double-quoted value")
+                    })? + body;
+                    let value = parser.expand(content, body, &content[body..close], true)?;
+                    (value, close + 1)
+                }
+                _ => {
+                    let raw = &content[value_start..end];
+                    let raw = raw
+                        .char_indices()
+                        .find(|&(i, c)| c == '#' && raw[..i].ends_with([' ', '\t']))
+                        .map_or(raw, |(comment, _)| &raw[..comment]);
+                    let value = parser.expand(content, value_start, raw.trim_end(), false)?;
+                    (value, end)
+                }
+            };
+            let rest_end = line_end(content, value_end);
+            let rest = content[value_end..rest_end].trim();
+            if !rest.is_empty() && !rest.starts_with('#') {
+                return Err(lexer::error_at(
+                    content,
+                    value_end,
+                    "unexpected text after the quoted value",
+                ));
+            }
+            parser.set(&section, key, value);
+            pos = rest_end + 1;
+        }
+        Ok(parser)
+    }
+
+    /**
+     * Serializes the properties of the default section as a `.env` file. Values made only of
+     * letters, digits and `_-./:,@+%` are written bare, all others double-quoted with
+     * escapes, so `from_dotenv` reads them back unchanged.
+     *
+     * # Returns
+     * Returns a `String` with one `KEY=value` line per property.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_dotenv("").unwrap();
+     * parser.set(dotini::DEFAULT_SECTION, "PORT", "8080");
+     * parser.set(dotini::DEFAULT_SECTION, "TITLE", "My \"$APP\"");
+     * assert_eq!(parser.to_dotenv(), "PORT=8080\nTITLE=\"My \\\"\\$APP\\\"\"\n");
+     * ```
+     */
+    pub fn to_dotenv(&self) -> String {
+        let mut out = String::new();
+        let Some(globals) = self.globals() else {
+            return out;
+        };
+        for (key, value) in globals.iter() {
+            out.push_str(key);
+            out.push('=');
+            if value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-./:,@+%".contains(c))
+            {
+                out.push_str(value);
+            } else {
+                out.push('"');
+                for c in value.chars() {
+                    match c {
+                        '\n' => out.push_str("\\n"),
+                        '\r' => out.push_str("\\r"),
+                        '\t' => out.push_str("\\t"),
+                        '"' | '\\' | '$' => {
+                            out.push('\\');
+                            out.push(c);
+                        }
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Replaces the `$NAME` and `${NAME}` references of a value, resolving backslash escapes
+    /// as well if `escapes` is set. `offset` is where `raw` starts in `content`.
+    fn expand(
+        &self,
+        content: &str,
+        offset: usize,
+        raw: &str,
+        escapes: bool,
+    ) -> INIParserResult<String> {
+        let mut expanded = String::with_capacity(raw.len());
+        let mut chars = raw.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' if escapes => match chars.next() {
+                    Some((_, 'n')) => expanded.push('\n'),
+                    Some((_, 'r')) => expanded.push('\r'),
+                    Some((_, 't')) => expanded.push('\t'),
+                    Some((_, c @ ('"' | '\\' | '$'))) => expanded.push(c),
+                    Some((_, c)) => {
+                        expanded.push('\\');
+                        expanded.push(c);
+                    }
+                    None => expanded.push('\\'),
+                },
+                '\\' if chars.peek().is_some_and(|&(_, c)| c == '$') => {
+                    chars.next();
+                    expanded.push('$');
+                }
+                '$' => {
+                    let rest = &raw[i + 1..];
+                    let (name, len) = match rest.strip_prefix('{') {
+                        Some(braced) => {
+                            let close = braced.find('}').ok_or_else(|| {
+                                lexer::error_at(content, offset + i, "unterminated `${` reference")
+                            })?;
+                            (&braced[..close], close + 2)
+                        }
+                        None => {
+                            let len = rest
+                                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                                .unwrap_or(rest.len());
+                            (&rest[..len], len)
+                        }
+                    };
+                    if len == 0 {
+                        expanded.push('$');
+                        continue;
+                    }
+                    match self.get(&self.options.default_section, name) {
+                        Some(value) => expanded.push_str(value),
+                        None => expanded.push_str(&env::var(name).unwrap_or_default()),
+                    }
+                    while chars.peek().is_some_and(|&(j, _)| j <= i + len) {
+                        chars.next();
+                    }
+                }
+                c => expanded.push(c),
+            }
+        }
+        Ok(expanded)
+    }
+}
+
+/// Returns the offset of the newline ending the line that contains `pos`, or the length of
+/// the content for the last line.
+fn line_end(content: &str, pos: usize) -> usize {
+    content[pos..].find('\n').map_or(content.len(), |i| pos + i)
+}
+
+/// Returns the offset of the `"` closing a double-quoted value, skipping escaped characters.
+fn closing_quote(body: &str) -> Option<usize> {
+    let mut chars = body.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some(i),
+            '\\' => {
+                chars.next();
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Returns `true` for the characters allowed in variable names.
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
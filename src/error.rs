@@ -0,0 +1,461 @@
+use crate::InIParseError;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+/**
+ * The details of an `InIParseError::UnsuccessfulParse`: what is wrong and where, so that
+ * applications can point users at the broken line.
+ *
+ * ```rust
+ * use dotini::{INIParser, InIParseError};
+ *
+ * let err = INIParser::from_string("[server]\nhost = localhost\n[broken\n").unwrap_err();
+ * let InIParseError::UnsuccessfulParse(err) = err else { panic!() };
+ * assert_eq!((err.line, err.column, err.offset), (3, 8, 33));
+ * assert_eq!(err.source_line, "[broken");
+ * assert_eq!(err.file, None);
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// What kind of problem it is.
+    pub kind: ParseErrorKind,
+    /// What is wrong, e.g. ``duplicate key `port` in section `server` ``.
+    pub message: String,
+    /// The line of the error, starting at 1.
+    pub line: usize,
+    /// The column of the error within its line, in characters and starting at 1.
+    pub column: usize,
+    /// The byte offset of the error in the text that was parsed.
+    pub offset: usize,
+    /// The line of the error as written, without its line terminator.
+    pub source_line: String,
+    /// The included file the error is in, or `None` for the text that was parsed itself.
+    pub file: Option<PathBuf>,
+}
+
+/// The kinds of `ParseError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A line that cannot be read with the enabled syntax.
+    Syntax,
+    /// A section repeated under `DuplicateSectionPolicy::Error`.
+    DuplicateSection,
+    /// A key repeated under `DuplicateKeyPolicy::Error`.
+    DuplicateKey,
+    /// A property before the first section header under `OrphanPolicy::Error`.
+    OrphanProperty,
+    /// An include directive naming a file that cannot be read, nested too deeply or forming
+    /// a cycle.
+    Include,
+}
+
+impl ParseErrorKind {
+    /// Returns the stable code of the kind, like `E0003` for `DuplicateKey`. See
+    /// `InIParseError::code`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Syntax => "E0001",
+            Self::DuplicateSection => "E0002",
+            Self::DuplicateKey => "E0003",
+            Self::OrphanProperty => "E0004",
+            Self::Include => "E0005",
+        }
+    }
+}
+
+impl ParseError {
+    /// Returns the stable code of the kind of the error.
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    /// Builds an error about the byte `offset` of `content`, moved back to the end of the text
+    /// if it lies beyond it.
+    pub(crate) fn new(content: &str, offset: usize, message: impl Into<String>) -> Self {
+        let mut offset = offset.min(content.len());
+        while !content.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = content[..offset].rfind('\n').map_or(0, |at| at + 1);
+        let line_end = content[offset..]
+            .find('\n')
+            .map_or(content.len(), |at| offset + at);
+        let source_line = &content[line_start..line_end];
+        Self {
+            kind: ParseErrorKind::Syntax,
+            message: message.into(),
+            line: content[..line_start].matches('\n').count() + 1,
+            column: content[line_start..offset].chars().count() + 1,
+            offset,
+            source_line: source_line
+                .strip_suffix('\r')
+                .unwrap_or(source_line)
+                .to_string(),
+            file: None,
+        }
+    }
+}
+
+/**
+ * The details of the errors of the typed getters like `INIParser::get_int`: which property
+ * holds a value that cannot be read as the expected type.
+ *
+ * ```rust
+ * use dotini::{INIParser, InIParseError};
+ *
+ * let parser = INIParser::from_string("[db]\nport = abc\n").unwrap();
+ * let InIParseError::InvalidNumber(err) = parser.get_int("db", "port").unwrap_err() else { panic!() };
+ * assert_eq!((err.section.as_str(), err.key.as_str(), err.value.as_str()), ("db", "port", "abc"));
+ * assert_eq!(err.expected, "integer");
+ * assert_eq!(err.to_string(), "db.port: expected integer, got 'abc' (invalid digit found in string)");
+ *
+ * let err = parser.get_as::<u16>("db", "port").unwrap_err();
+ * assert!(err.to_string().starts_with("db.port: expected u16, got 'abc'"));
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError {
+    /// The section of the property.
+    pub section: String,
+    /// The name of the property.
+    pub key: String,
+    /// The value as stored.
+    pub value: String,
+    /// The type the value was read as, e.g. `integer` or `boolean`.
+    pub expected: String,
+    /// Why the value could not be read, when more is known than its type.
+    pub reason: Option<String>,
+}
+
+impl ValueError {
+    pub(crate) fn new(
+        section: &str,
+        key: &str,
+        value: &str,
+        expected: &str,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            section: section.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+            expected: expected.to_string(),
+            reason,
+        }
+    }
+}
+
+/// Formats as `section.key: expected type, got 'value'`, followed by the reason in parentheses.
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}: expected {}, got '{}'",
+            self.section, self.key, self.expected, self.value
+        )?;
+        match &self.reason {
+            Some(reason) => write!(f, " ({reason})"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Error for ValueError {}
+
+/// The kinds of `Warning`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// A repeated key replaced an earlier value, or was ignored under
+    /// `DuplicateKeyPolicy::KeepFirst`.
+    DuplicateKey,
+    /// A section name, key or value contains whitespace or an invisible character other than
+    /// spaces and tabs, such as a no-break or zero-width space pasted along with it.
+    SuspiciousWhitespace,
+    /// A section header is not followed by any property.
+    EmptySection,
+    /// A key registered with `ParserOptions::deprecated_key` or `ParserOptions::migrate_key`
+    /// was written.
+    Deprecated,
+    /// A property under `ParserOptions::strict` has nothing after its delimiter, such as
+    /// `key =`.
+    EmptyValue,
+}
+
+impl WarningKind {
+    /**
+     * Returns the stable code of the kind, so tooling can match on warnings without relying
+     * on their messages.
+     *
+     * | Code    | Kind                   |
+     * |---------|------------------------|
+     * | `W0001` | `DuplicateKey`         |
+     * | `W0002` | `SuspiciousWhitespace` |
+     * | `W0003` | `EmptySection`         |
+     * | `W0004` | `Deprecated`           |
+     * | `W0005` | `EmptyValue`           |
+     */
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::DuplicateKey => "W0001",
+            Self::SuspiciousWhitespace => "W0002",
+            Self::EmptySection => "W0003",
+            Self::Deprecated => "W0004",
+            Self::EmptyValue => "W0005",
+        }
+    }
+}
+
+/**
+ * Something suspicious in the parsed text that did not stop the parse, reported by
+ * `INIParser::warnings`.
+ *
+ * ```rust
+ * use dotini::{INIParser, WarningKind};
+ *
+ * let content = "[server]\nport = 80\nport = 8080\nname = Jane\u{a0}Doe\n[unused]\n";
+ * let parser = INIParser::from_string(content).unwrap();
+ * let warnings: Vec<_> = parser.warnings().iter().map(|warning| (warning.kind, warning.line)).collect();
+ * assert_eq!(
+ *     warnings,
+ *     [
+ *         (WarningKind::DuplicateKey, 3),
+ *         (WarningKind::SuspiciousWhitespace, 4),
+ *         (WarningKind::EmptySection, 5),
+ *     ]
+ * );
+ * assert_eq!(parser.warnings()[0].to_string(), "3:1: key `port` in section `server` is repeated, replacing its earlier value");
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    /// What is suspicious.
+    pub message: String,
+    /// The line of the warning, starting at 1.
+    pub line: usize,
+    /// The column of the warning within its line, in characters and starting at 1.
+    pub column: usize,
+    /// The byte offset of the warning in the text that was parsed.
+    pub offset: usize,
+    /// The line of the warning as written, without its line terminator.
+    pub source_line: String,
+    /// The included file the warning is about, or `None` for the text that was parsed itself.
+    pub file: Option<PathBuf>,
+}
+
+impl Warning {
+    /// Returns the stable code of the kind of the warning, see `WarningKind::code`.
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    /// Builds a warning about the byte `offset` of `content`.
+    pub(crate) fn new(
+        content: &str,
+        offset: usize,
+        kind: WarningKind,
+        message: impl Into<String>,
+    ) -> Self {
+        let at = ParseError::new(content, offset, message);
+        Self {
+            kind,
+            message: at.message,
+            line: at.line,
+            column: at.column,
+            offset: at.offset,
+            source_line: at.source_line,
+            file: None,
+        }
+    }
+}
+
+/// Formats as `line:column: message`, preceded by the included file the warning is about.
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for Warning {}
+
+impl ParseError {
+    /// Writes the line of the error as written, with a caret under its column.
+    fn write_snippet(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        // Tabs are kept so the caret lines up with the line whatever the tab width.
+        let indent: String = self
+            .source_line
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        write!(f, "\n{gutter} |\n{number} | {}", self.source_line)?;
+        write!(f, "\n{gutter} | {indent}^")
+    }
+}
+
+/// Formats as `line:column: message`, preceded by the included file the error is in.
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/**
+ * Formats a message describing the error, so it can be reported as is or wrapped into other
+ * error types with `?`. Parse errors are followed by the offending line with a caret under
+ * the column of the error.
+ *
+ * ```rust
+ * use dotini::INIParser;
+ *
+ * fn port() -> Result<i64, Box<dyn std::error::Error>> {
+ *     let parser = INIParser::from_string("[server]\nport = eighty\n")?;
+ *     Ok(parser.get_int("server", "port")?)
+ * }
+ *
+ * let err = port().unwrap_err();
+ * assert_eq!(err.to_string(), "server.port: expected integer, got 'eighty' (invalid digit found in string)");
+ *
+ * let err = INIParser::from_string("[server]\nport 80\n").unwrap_err();
+ * assert_eq!(
+ *     err.to_string(),
+ *     "parse error at 2:5: expected a delimiter; keys with spaces are disabled\n  |\n2 | port 80\n  |     ^"
+ * );
+ *
+ * let err = INIParser::from_string("[server]\n\tport 80\n").unwrap_err();
+ * assert!(err.to_string().ends_with("\n2 | \tport 80\n  | \t    ^"));
+ * ```
+ */
+impl fmt::Display for InIParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileReadError(err) => write!(f, "cannot read file: {err}"),
+            Self::FileWriteError(err) => write!(f, "cannot write file: {err}"),
+            Self::SerializationError(err) => write!(f, "cannot serialize config: {err}"),
+            Self::DeserializationError(err) => write!(f, "cannot deserialize config: {err}"),
+            Self::UnsuccessfulParse(err) => {
+                write!(f, "parse error at {err}")?;
+                err.write_snippet(f)
+            }
+            Self::MissingKey(key) => write!(f, "missing key {key}"),
+            Self::ConversionError(err) | Self::InvalidBool(err) | Self::InvalidNumber(err) => {
+                write!(f, "{err}")
+            }
+            Self::InterpolationError(message) => f.write_str(message),
+            Self::InterpolationTooLarge { property, limit } => write!(
+                f,
+                "interpolated value of {property} exceeds the limit of {limit} bytes"
+            ),
+            Self::InputTooLarge { size, limit } => {
+                write!(
+                    f,
+                    "input of {size} bytes exceeds the limit of {limit} bytes"
+                )
+            }
+            Self::LineTooLong {
+                line,
+                length,
+                limit,
+            } => write!(
+                f,
+                "line {line} is {length} bytes long, exceeding the limit of {limit} bytes"
+            ),
+            Self::TooManySections { limit } => write!(f, "more than {limit} sections"),
+            Self::TooManyKeys { section, limit } => {
+                write!(f, "more than {limit} keys in section `{section}`")
+            }
+            Self::Multiple(errors) => {
+                let plural = if errors.len() == 1 { "" } else { "s" };
+                write!(f, "{} error{plural}:", errors.len())?;
+                for err in errors {
+                    for line in err.to_string().lines() {
+                        write!(f, "\n  {line}")?;
+                    }
+                }
+                Ok(())
+            }
+            Self::Finished => f.write_str("unexpected end of the parse tree"),
+            Self::Unreachable => f.write_str("internal parser error"),
+        }
+    }
+}
+
+impl InIParseError {
+    /**
+     * Returns a stable code for the kind of the error, so tooling and tests can match on
+     * categories rather than messages. Parse errors take the code of their `ParseErrorKind`.
+     *
+     * | Code    | Error                                 |
+     * |---------|---------------------------------------|
+     * | `E0001` | `ParseErrorKind::Syntax`              |
+     * | `E0002` | `ParseErrorKind::DuplicateSection`    |
+     * | `E0003` | `ParseErrorKind::DuplicateKey`        |
+     * | `E0004` | `ParseErrorKind::OrphanProperty`      |
+     * | `E0005` | `ParseErrorKind::Include`             |
+     * | `E0010` | `FileReadError`                       |
+     * | `E0011` | `FileWriteError`                      |
+     * | `E0012` | `SerializationError`                  |
+     * | `E0013` | `DeserializationError`                |
+     * | `E0020` | `MissingKey`                          |
+     * | `E0021` | `ConversionError`                     |
+     * | `E0022` | `InvalidBool`                         |
+     * | `E0023` | `InvalidNumber`                       |
+     * | `E0024` | `InterpolationError`                  |
+     * | `E0025` | `InterpolationTooLarge`               |
+     * | `E0030` | `InputTooLarge`                       |
+     * | `E0031` | `LineTooLong`                         |
+     * | `E0032` | `TooManySections`                     |
+     * | `E0033` | `TooManyKeys`                         |
+     * | `E0040` | `Multiple`                            |
+     * | `E0090` | `Finished`                            |
+     * | `E0091` | `Unreachable`                         |
+     *
+     * ```rust
+     * use dotini::{DuplicateKeyPolicy, INIParser, ParserOptions};
+     *
+     * let options = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+     * let err = INIParser::from_string_with_options("[db]\nport = 1\nport = 2\n", options).unwrap_err();
+     * assert_eq!(err.code(), "E0003");
+     *
+     * let parser = INIParser::from_string("[db]\nport = 1\nport = 2\n").unwrap();
+     * assert_eq!(parser.warnings()[0].code(), "W0001");
+     * assert_eq!(parser.get_bool("db", "port").unwrap_err().code(), "E0022");
+     * ```
+     */
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnsuccessfulParse(err) => err.code(),
+            Self::FileReadError(_) => "E0010",
+            Self::FileWriteError(_) => "E0011",
+            Self::SerializationError(_) => "E0012",
+            Self::DeserializationError(_) => "E0013",
+            Self::MissingKey(_) => "E0020",
+            Self::ConversionError(_) => "E0021",
+            Self::InvalidBool(_) => "E0022",
+            Self::InvalidNumber(_) => "E0023",
+            Self::InterpolationError(_) => "E0024",
+            Self::InterpolationTooLarge { .. } => "E0025",
+            Self::InputTooLarge { .. } => "E0030",
+            Self::LineTooLong { .. } => "E0031",
+            Self::TooManySections { .. } => "E0032",
+            Self::TooManyKeys { .. } => "E0033",
+            Self::Multiple(_) => "E0040",
+            Self::Finished => "E0090",
+            Self::Unreachable => "E0091",
+        }
+    }
+}
+
+impl Error for InIParseError {}
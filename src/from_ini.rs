@@ -0,0 +1,54 @@
+use crate::{INIParser, INIParserResult};
+
+/**
+ * Types that can be read from a config without serde, usually through
+ * `#[derive(FromIni)]` with the `derive` feature.
+ *
+ * The derive reads each field from the property of the same name with `INIParser::get_as`, so
+ * any type implementing `FromStr` can be used. An `Option` field is `None` when its property
+ * is missing, and a field marked `#[ini(section)]` is read from the section of the same name
+ * with its own `FromIni` implementation. `#[ini(rename = "...", alias = "...", default = ...)]`
+ * map fields onto other names and give them defaults.
+ *
+ * ```rust
+ * use dotini::{FromIni, INIParser, INIParserResult};
+ *
+ * struct Server {
+ *     host: String,
+ *     port: u16,
+ * }
+ *
+ * impl FromIni for Server {
+ *     fn from_section(parser: &INIParser, section: &str) -> INIParserResult<Self> {
+ *         Ok(Server {
+ *             host: parser.get_as(section, "host")?,
+ *             port: parser.get_as(section, "port")?,
+ *         })
+ *     }
+ * }
+ *
+ * let parser = INIParser::from_string("[server]\nhost = localhost\nport = 8080\n").unwrap();
+ * let server = Server::from_section(&parser, "server").unwrap();
+ * assert_eq!((server.host.as_str(), server.port), ("localhost", 8080));
+ * ```
+ */
+pub trait FromIni: Sized {
+    /**
+     * Reads the value from the properties of a section.
+     *
+     * # Arguments
+     * * `parser` - The config to read from.
+     * * `section` - The name of the section holding the properties.
+     *
+     * # Returns
+     * Returns the value, or the error of the first property that is missing or cannot be
+     * converted.
+     */
+    fn from_section(parser: &INIParser, section: &str) -> INIParserResult<Self>;
+
+    /// Reads the value from a whole config, its plain fields coming from the default section,
+    /// see `ParserOptions::default_section`.
+    fn from_ini(parser: &INIParser) -> INIParserResult<Self> {
+        Self::from_section(parser, &parser.options.default_section)
+    }
+}
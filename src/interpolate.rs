@@ -0,0 +1,256 @@
+use crate::{INIParser, INIParserResult, InIParseError, Interpolation};
+use std::collections::HashMap;
+use std::env;
+
+/// The state of resolving the references of one or more values.
+#[derive(Default)]
+struct Resolution {
+    /// The `section:key` names of the properties being resolved, outermost first, to detect
+    /// cycles.
+    chain: Vec<String>,
+    /// The values already resolved, by `section:key` name, so that a property referenced many
+    /// times over is only expanded once, with the number of levels of references they follow.
+    resolved: HashMap<String, (String, usize)>,
+}
+
+impl Resolution {
+    /// Starts resolving the value of a property.
+    fn start(&mut self, section: &str, key: &str) -> &mut Self {
+        self.chain.clear();
+        self.chain.push(format!("{section}:{key}"));
+        self
+    }
+}
+
+impl INIParser {
+    /**
+     * Returns the value of a property with its `${key}` and `${section:key}` references
+     * replaced by the values they name, as in Python's `ExtendedInterpolation`. A reference
+     * without a section names a key of the same section, and referenced values are themselves
+     * interpolated, up to `ParserOptions::max_interpolation_depth` levels deep. `$$` stands
+     * for a literal `$`. `ParserOptions::interpolation` selects other syntaxes.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns `Ok(None)` if the property does not exist, `InIParseError::InterpolationError`
+     * for a reference that is unterminated, names a missing property, refers back to itself or
+     * is nested too deeply, or `InIParseError::InterpolationTooLarge` if the value expands
+     * past `ParserOptions::max_interpolated_length`.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[paths]\nhome = /srv/app\ndata = ${home}/data\n[log]\ndir = ${paths:data}/logs\nfile = ${dir}/app.log\nloop = ${loop}\nbroken = ${paths:tmp}/x\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("log", "file"), Some("${dir}/app.log"));
+     * assert_eq!(parser.get_interpolated("log", "file").unwrap().as_deref(), Some("/srv/app/data/logs/app.log"));
+     * assert!(parser.get_interpolated("log", "loop").is_err());
+     * assert!(parser.get_interpolated("log", "broken").is_err());
+     * assert_eq!(parser.get_interpolated("log", "missing").unwrap(), None);
+     *
+     * let parser = INIParser::from_string("[price]\nunit = USD\nlabel = $$5 ${unit}\n").unwrap();
+     * assert_eq!(parser.get_interpolated("price", "label").unwrap().as_deref(), Some("$5 USD"));
+     * ```
+     */
+    pub fn get_interpolated(&self, section: &str, key: &str) -> INIParserResult<Option<String>> {
+        match self.get(section, key) {
+            Some(value) => {
+                let mut resolution = Resolution::default();
+                self.interpolate_value(value, section, resolution.start(section, key))
+                    .map(|(value, _)| Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /**
+     * Replaces the references of every value in place, as `get_interpolated` would resolve
+     * them. Either every value is resolved or, on error, the config is left unchanged.
+     *
+     * # Returns
+     * Returns `InIParseError::InterpolationError` for the first reference that cannot be
+     * resolved, or `InIParseError::InterpolationTooLarge` for the first value expanding past
+     * `ParserOptions::max_interpolated_length`.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[app]\nname = demo\ntitle = ${name} server\n").unwrap();
+     * parser.interpolate().unwrap();
+     * assert_eq!(parser.get("app", "title"), Some("demo server"));
+     * ```
+     */
+    pub fn interpolate(&mut self) -> INIParserResult<()> {
+        let mut resolution = Resolution::default();
+        let mut resolved = Vec::new();
+        for (section, key, value) in self.iter() {
+            let (value, _) =
+                self.interpolate_value(value, section, resolution.start(section, key))?;
+            resolved.push(value);
+        }
+        let mut lists = Vec::new();
+        for (section, keys) in &self.multi_values {
+            for (key, values) in keys {
+                let values = values
+                    .iter()
+                    .map(|value| {
+                        self.interpolate_value(value, section, resolution.start(section, key))
+                            .map(|(value, _)| value)
+                    })
+                    .collect::<INIParserResult<Vec<_>>>()?;
+                lists.push((section.clone(), key.clone(), values));
+            }
+        }
+        let values = self
+            .output
+            .values_mut()
+            .flat_map(|properties| properties.values_mut());
+        for (value, resolved) in values.zip(resolved) {
+            *value = resolved;
+        }
+        for (section, key, values) in lists {
+            if let Some(keys) = self.multi_values.get_mut(&section) {
+                keys.insert(key, values);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the references of a value of `section`, the innermost property of the chain of
+    /// the resolution. Returns the value together with the number of levels of references it
+    /// followed.
+    fn interpolate_value(
+        &self,
+        value: &str,
+        section: &str,
+        resolution: &mut Resolution,
+    ) -> INIParserResult<(String, usize)> {
+        match self.options.interpolation {
+            Interpolation::None => return Ok((value.to_string(), 0)),
+            Interpolation::Windows => return Ok((expand_windows(value), 0)),
+            Interpolation::Extended => (),
+        }
+        let depth = self.options.max_interpolation_depth;
+        let too_deep = |chain: &[String]| {
+            InIParseError::InterpolationError(format!(
+                "interpolation of {} is nested too deeply, the limit is {depth}",
+                chain[0]
+            ))
+        };
+        let error = |message: String| InIParseError::InterpolationError(message);
+        let limit = self.options.max_interpolated_length;
+        let too_large = |chain: &[String]| InIParseError::InterpolationTooLarge {
+            property: chain[0].clone(),
+            limit,
+        };
+        let mut expanded = String::with_capacity(value.len());
+        let mut levels = 0;
+        let mut rest = value;
+        while let Some(start) = rest.find('$') {
+            if expanded.len() > limit {
+                return Err(too_large(&resolution.chain));
+            }
+            expanded.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            // `$$` stands for a literal `$`, and a `$` not starting a reference is kept.
+            let Some(after) = after.strip_prefix('{') else {
+                expanded.push('$');
+                rest = after.strip_prefix('$').unwrap_or(after);
+                continue;
+            };
+            let chain = &mut resolution.chain;
+            let current = chain.last().map_or("", String::as_str);
+            let end = after
+                .find('}')
+                .ok_or_else(|| error(format!("unterminated `${{` in the value of {current}")))?;
+            let reference = &after[..end];
+            rest = &after[end + 1..];
+            let variable = reference
+                .strip_prefix("ENV:")
+                .or_else(|| reference.strip_prefix("env."))
+                .filter(|_| self.options.env_interpolation);
+            if let Some(variable) = variable {
+                let value = env::var(variable).map_err(|_| {
+                    error(format!(
+                        "environment variable `{variable}` referenced in the value of {current} is not set"
+                    ))
+                })?;
+                expanded.push_str(&value);
+                continue;
+            }
+            let (target_section, target_key) =
+                reference.split_once(':').unwrap_or((section, reference));
+            let name = format!("{target_section}:{target_key}");
+            // A value resolved before is reused as long as its references stay within the depth.
+            if let Some((value, below)) = resolution.resolved.get(&name) {
+                if chain.len() + below > depth {
+                    return Err(too_deep(chain));
+                }
+                expanded.push_str(value);
+                levels = levels.max(below + 1);
+                continue;
+            }
+            if chain.len() > depth {
+                return Err(too_deep(chain));
+            }
+            if chain.contains(&name) {
+                return Err(error(format!(
+                    "interpolation cycle {} -> {name}",
+                    chain.join(" -> ")
+                )));
+            }
+            let target = self.get(target_section, target_key).ok_or_else(|| {
+                error(format!(
+                    "unresolved reference `${{{reference}}}` in the value of {current}"
+                ))
+            })?;
+            chain.push(name.clone());
+            let (value, below) = self.interpolate_value(target, target_section, resolution)?;
+            resolution.chain.pop();
+            expanded.push_str(&value);
+            levels = levels.max(below + 1);
+            resolution.resolved.insert(name, (value, below));
+        }
+        expanded.push_str(rest);
+        if expanded.len() > limit {
+            return Err(too_large(&resolution.chain));
+        }
+        Ok((expanded, levels))
+    }
+}
+
+/// Replaces the `%NAME%` references of a value by environment variables, keeping those that
+/// are not set and reading `%%` as a literal `%`.
+fn expand_windows(value: &str) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(after) = after.strip_prefix('%') {
+            expanded.push('%');
+            rest = after;
+            continue;
+        }
+        let Some(end) = after.find('%') else {
+            expanded.push('%');
+            rest = after;
+            continue;
+        };
+        let name = &after[..end];
+        match env::var(name) {
+            Ok(value) => expanded.push_str(&value),
+            Err(_) => {
+                expanded.push('%');
+                expanded.push_str(name);
+                expanded.push('%');
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
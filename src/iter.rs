@@ -0,0 +1,68 @@
+use crate::{Properties, Sections};
+use indexmap::map;
+
+/// An iterator over the `(section, key, value)` triples of a config.
+/// Created by `INIParser::iter`.
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    sections: map::Iter<'a, String, Properties>,
+    current: Option<(&'a str, map::Iter<'a, String, String>)>,
+}
+
+impl<'a> Iter<'a> {
+    pub(crate) fn new(output: &'a Sections) -> Self {
+        Self {
+            sections: output.iter(),
+            current: None,
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((section, properties)) = &mut self.current {
+                if let Some((key, value)) = properties.next() {
+                    return Some((section, key, value));
+                }
+            }
+            let (section, properties) = self.sections.next()?;
+            self.current = Some((section, properties.iter()));
+        }
+    }
+}
+
+/// An owning iterator over the `(section, key, value)` triples of a config.
+/// Created by the `IntoIterator` implementation of `INIParser`.
+#[derive(Debug)]
+pub struct IntoIter {
+    sections: map::IntoIter<String, Properties>,
+    current: Option<(String, map::IntoIter<String, String>)>,
+}
+
+impl IntoIter {
+    pub(crate) fn new(output: Sections) -> Self {
+        Self {
+            sections: output.into_iter(),
+            current: None,
+        }
+    }
+}
+
+impl Iterator for IntoIter {
+    type Item = (String, String, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((section, properties)) = &mut self.current {
+                if let Some((key, value)) = properties.next() {
+                    return Some((section.clone(), key, value));
+                }
+            }
+            let (section, properties) = self.sections.next()?;
+            self.current = Some((section, properties.into_iter()));
+        }
+    }
+}
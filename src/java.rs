@@ -0,0 +1,178 @@
+use crate::lexer::unicode_escape;
+use crate::{INIParser, Sections};
+use std::fmt::Write;
+
+impl INIParser {
+    /**
+     * Parses the content of a Java `.properties` file.
+     *
+     * Keys are separated from their values by `=`, `:` or whitespace, lines starting with `#`
+     * or `!` are comments, a line ending with a backslash continues on the next one, and the
+     * `\t`, `\n`, `\r`, `\f` and `\uXXXX` escapes are resolved in keys and values. Like
+     * `java.util.Properties`, the format has no syntax errors.
+     *
+     * # Arguments
+     * * `content` - The text of the `.properties` file.
+     * * `dotted_sections` - Maps dotted keys into sections at their last dot, so `db.host`
+     *   becomes the `host` key of the `db` section. Otherwise, and for keys without a dot,
+     *   properties go to the default section.
+     *
+     * # Returns
+     * Returns an `INIParser` holding the properties, which `to_properties` writes back.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "# app settings\napp.name = Caf\\u00e9\ndb.url: jdbc:postgresql://localhost/app\ndb.pool.size 10\npaths=/usr/lib,\\\n      /opt/lib\n";
+     * let parser = INIParser::from_properties(content, true);
+     * assert_eq!(parser.get("app", "name"), Some("Caf\u{e9}"));
+     * assert_eq!(parser.get("db", "url"), Some("jdbc:postgresql://localhost/app"));
+     * assert_eq!(parser.get("db.pool", "size"), Some("10"));
+     * assert_eq!(parser.globals().unwrap().get("paths"), Some("/usr/lib,/opt/lib"));
+     * assert_eq!(
+     *     parser.to_properties(),
+     *     "app.name=Caf\\u00E9\ndb.url=jdbc\\:postgresql\\://localhost/app\ndb.pool.size=10\npaths=/usr/lib,/opt/lib\n"
+     * );
+     * ```
+     */
+    pub fn from_properties(content: &str, dotted_sections: bool) -> Self {
+        let mut parser = Self::from_map(Sections::new());
+        let default_section = parser.options.default_section.clone();
+        for line in logical_lines(content) {
+            let (key, value) = split_property(&line);
+            let (key, value) = (unescape(key), unescape(value));
+            let (section, key) = match key.rsplit_once('.') {
+                Some((section, key))
+                    if dotted_sections && !section.is_empty() && !key.is_empty() =>
+                {
+                    (section, key)
+                }
+                _ => (default_section.as_str(), key.as_str()),
+            };
+            parser.set(section, key, value);
+        }
+        parser
+    }
+
+    /**
+     * Serializes the config as a Java `.properties` file, the way `java.util.Properties`
+     * stores it: properties of the default section are written with their plain key and
+     * every other property as `section.key`, with special and non-ASCII characters escaped.
+     *
+     * # Returns
+     * Returns a `String` with one `key=value` line per property.
+     */
+    pub fn to_properties(&self) -> String {
+        let mut out = String::new();
+        let default_section = self.options.default_section.as_str();
+        for (section, key, value) in self.iter() {
+            if section != default_section {
+                escape_into(&mut out, section, true);
+                out.push('.');
+            }
+            escape_into(&mut out, key, true);
+            out.push('=');
+            escape_into(&mut out, value, false);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Splits `.properties` text into logical lines, dropping blank and comment lines and joining
+/// lines continued by an odd number of trailing backslashes. Continuation lines lose their
+/// leading whitespace.
+fn logical_lines(content: &str) -> impl Iterator<Item = String> + '_ {
+    let mut physical = content
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line));
+    std::iter::from_fn(move || loop {
+        let first = physical.next()?.trim_start_matches([' ', '\t', '\x0c']);
+        if first.is_empty() || first.starts_with(['#', '!']) {
+            continue;
+        }
+        let mut line = first.to_string();
+        while continues(&line) {
+            line.pop();
+            match physical.next() {
+                Some(next) => line.push_str(next.trim_start_matches([' ', '\t', '\x0c'])),
+                None => break,
+            }
+        }
+        return Some(line);
+    })
+}
+
+/// Returns `true` if a line ends with an odd number of backslashes.
+fn continues(line: &str) -> bool {
+    line.bytes().rev().take_while(|&b| b == b'\\').count() % 2 == 1
+}
+
+/// Splits a logical line at the first unescaped `=`, `:` or whitespace, skipping the
+/// whitespace around the separator.
+fn split_property(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    let end = line
+        .char_indices()
+        .find(|&(_, c)| {
+            let separator = !escaped && matches!(c, '=' | ':' | ' ' | '\t' | '\x0c');
+            escaped = !escaped && c == '\\';
+            separator
+        })
+        .map_or(line.len(), |(i, _)| i);
+    let rest = line[end..].trim_start_matches([' ', '\t', '\x0c']);
+    let rest = rest.strip_prefix(['=', ':']).unwrap_or(rest);
+    (&line[..end], rest.trim_start_matches([' ', '\t', '\x0c']))
+}
+
+/// Resolves the escapes of a key or value. A backslash before any other character stands
+/// for that character.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('f') => unescaped.push('\x0c'),
+            Some('u') => match unicode_escape('u', chars.as_str()) {
+                Some((decoded, len)) => {
+                    unescaped.push(decoded);
+                    chars = chars.as_str()[len..].chars();
+                }
+                None => unescaped.push('u'),
+            },
+            Some(c) => unescaped.push(c),
+            None => (),
+        }
+    }
+    unescaped
+}
+
+/// Appends a key or value with the escapes `java.util.Properties` uses when storing.
+fn escape_into(out: &mut String, text: &str, key: bool) {
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            ' ' if key || i == 0 => out.push_str("\\ "),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\x0c' => out.push_str("\\f"),
+            '\\' | '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            c => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    let _ = write!(out, "\\u{unit:04X}");
+                }
+            }
+        }
+    }
+}
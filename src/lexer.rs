@@ -0,0 +1,698 @@
+use crate::section::subsection_name;
+use crate::{
+    INIParserResult, InIParseError, Ini, ParseError, ParseErrorKind, ParserOptions, Rule,
+    TrimPolicy, ValuelessKeys,
+};
+use pest::error::{Error, InputLocation};
+use pest::iterators::Pair;
+use pest::Parser;
+use std::ops::Range;
+
+/// A line of the input, without its line terminator. With `ParserOptions::line_continuation`
+/// or `ParserOptions::multiline_values`, a property extends over the physical lines that
+/// continue its value.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RawLine<'a> {
+    /// The byte offset of the line in the input.
+    pub start: usize,
+    pub text: &'a str,
+    /// The line terminator, empty for a last line without one.
+    pub eol: &'a str,
+}
+
+/// What a single line contains. Ranges are byte ranges into `RawLine::text`; the spans of a
+/// property cover its key and value as written, without any inline comment or trailing
+/// whitespace, while `key` and `value` hold them with quotes and escapes resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    Blank,
+    Comment,
+    Section {
+        name_span: Range<usize>,
+        name: String,
+        parent: Option<String>,
+        /// `false` for a conditional section whose conditions do not all hold.
+        selected: bool,
+    },
+    Include {
+        path_span: Range<usize>,
+        path: String,
+        /// `true` for an `!includedir` directive naming a directory.
+        dir: bool,
+    },
+    Property {
+        key_span: Range<usize>,
+        key: String,
+        value_span: Range<usize>,
+        value: String,
+    },
+}
+
+/// Splits the input into lines, keeping track of their terminators.
+pub(crate) fn lines<'a>(
+    content: &'a str,
+    options: &'a ParserOptions,
+) -> impl Iterator<Item = RawLine<'a>> {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start >= content.len() {
+            return None;
+        }
+        let mut end = start;
+        loop {
+            let rest = &content[end..];
+            let (len, eol) = match rest.find('\n') {
+                Some(len) if rest[..len].ends_with('\r') => (len - 1, "\r\n"),
+                Some(len) => (len, "\n"),
+                None => (rest.len(), ""),
+            };
+            let text = &content[start..end + len];
+            let next = &content[end + len + eol.len()..];
+            if !eol.is_empty() && continues(text, next, options) {
+                end += len + eol.len();
+                continue;
+            }
+            let line = RawLine { start, text, eol };
+            start = end + len + eol.len();
+            return Some(line);
+        }
+    })
+}
+
+/// Returns `true` if the line `text`, which may already span several physical lines, continues
+/// onto the `next` one, either by ending with a backslash or because the next line is indented
+/// deeper than the start of the property.
+fn continues(text: &str, next: &str, options: &ParserOptions) -> bool {
+    if options.is_comment(text) {
+        return false;
+    }
+    if options.line_continuation && ends_with_backslash(text) {
+        return true;
+    }
+    // systemd skips comment lines inside a continued value and keeps reading after them.
+    if options.systemd_continuation && text.contains('\n') {
+        let last = text.rsplit('\n').next().unwrap_or(text);
+        if options.is_comment(last) {
+            return true;
+        }
+    }
+    let next = &next[..next.find('\n').unwrap_or(next.len())];
+    let next = next.strip_suffix('\r').unwrap_or(next);
+    let first = text.trim_start_matches([' ', '\t']);
+    options.multiline_values
+        && !first.is_empty()
+        && !first.starts_with('[')
+        && !next.trim().is_empty()
+        && !options.is_comment(next)
+        && indent(next) > indent(text)
+}
+
+/// Returns `true` if a line ends with an odd number of backslashes.
+fn ends_with_backslash(text: &str) -> bool {
+    (text.len() - text.trim_end_matches('\\').len()) % 2 == 1
+}
+
+/// Returns the number of spaces and tabs a line starts with.
+fn indent(text: &str) -> usize {
+    text.len() - text.trim_start_matches([' ', '\t']).len()
+}
+
+/// A logical line of a value, joined from one or more physical lines.
+struct JoinedLine {
+    text: String,
+    /// Where each of the joined physical lines starts, as offsets in `text` and in the value
+    /// as written.
+    starts: Vec<(usize, usize)>,
+}
+
+impl JoinedLine {
+    /// Returns the offset in the value as written of the byte at `offset` in `text`.
+    fn raw_offset(&self, offset: usize) -> usize {
+        let (start, raw_start) = self
+            .starts
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= offset)
+            .copied()
+            .unwrap_or_default();
+        raw_start + offset - start
+    }
+}
+
+/// Joins the physical lines of a value into its logical lines. Lines continued with a
+/// backslash are joined without the backslash, the line break and the indentation of the
+/// following line; other lines start a new logical line. With
+/// `ParserOptions::systemd_continuation` the backslash becomes a space and comment lines
+/// inside the continuation are dropped.
+fn join_lines(raw: &str, options: &ParserOptions) -> Vec<JoinedLine> {
+    let mut lines: Vec<JoinedLine> = Vec::new();
+    let mut continued = false;
+    let mut at = 0;
+    for piece in raw.split('\n') {
+        let start = at;
+        at += piece.len() + 1;
+        let piece = piece.strip_suffix('\r').unwrap_or(piece);
+        if continued && options.systemd_continuation && options.is_comment(piece) {
+            continue;
+        }
+        let piece = match lines.last_mut() {
+            Some(line) if continued => {
+                let indented = piece;
+                let piece = piece.trim_start_matches([' ', '\t']);
+                let start = start + indented.len() - piece.len();
+                line.starts.push((line.text.len(), start));
+                line.text.push_str(piece);
+                piece
+            }
+            _ => {
+                lines.push(JoinedLine {
+                    text: piece.to_string(),
+                    starts: vec![(0, start)],
+                });
+                piece
+            }
+        };
+        continued = options.line_continuation && ends_with_backslash(piece);
+        if let Some(line) = lines.last_mut().filter(|_| continued) {
+            line.text.pop();
+            if options.systemd_continuation {
+                line.text.push(' ');
+            }
+        }
+    }
+    lines
+}
+
+/// Rejects input larger than `ParserOptions::max_input_size` before any of it is parsed.
+pub(crate) fn check_size(content: &str, options: &ParserOptions) -> INIParserResult<()> {
+    match options.max_input_size {
+        Some(limit) if content.len() > limit => Err(InIParseError::InputTooLarge {
+            size: content.len(),
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Finds the first whitespace or invisible character of a line other than spaces, tabs and
+/// line breaks, which is most likely pasted by accident. Returns its byte offset in the line.
+pub(crate) fn suspicious_whitespace(text: &str) -> Option<(usize, char)> {
+    text.char_indices().find(|&(_, c)| {
+        (c.is_whitespace() && !matches!(c, ' ' | '\t' | '\n' | '\r'))
+            || matches!(c, '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}')
+    })
+}
+
+/// Parses a single line with the `line` rule of the grammar, once comments are ruled out, and
+/// checks its length against `ParserOptions::max_line_length` and its key against
+/// `ParserOptions::key_chars`. Errors point at the position of the line in the whole input.
+pub(crate) fn tokenize(
+    content: &str,
+    line: &RawLine,
+    options: &ParserOptions,
+) -> INIParserResult<Token> {
+    if let Some(limit) = options.max_line_length {
+        if line.text.len() > limit {
+            return Err(InIParseError::LineTooLong {
+                line: content[..line.start].matches('\n').count() + 1,
+                length: line.text.len(),
+                limit,
+            });
+        }
+    }
+    let token = read_line(content, line, options)?;
+    if let (Some(allowed), Token::Property { key_span, key, .. }) = (options.key_chars, &token) {
+        if let Some(c) = key.chars().find(|&c| !allowed(c)) {
+            let written = &line.text[key_span.clone()];
+            let offset = key_span.start + written.find(c).unwrap_or(0);
+            return Err(error_at(
+                content,
+                line.start + offset,
+                &format!("character {c:?} is not allowed in keys"),
+            ));
+        }
+    }
+    Ok(token)
+}
+
+fn read_line(content: &str, line: &RawLine, options: &ParserOptions) -> INIParserResult<Token> {
+    if options.is_comment(line.text) {
+        return Ok(Token::Comment);
+    }
+    if options.glob_sections {
+        let text = line.text.trim_start_matches([' ', '\t']);
+        let header = text.trim_end_matches([' ', '\t']);
+        if let Some(name) = header
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            let start = line.text.len() - text.len() + 1;
+            return Ok(Token::Section {
+                name_span: start..start + name.len(),
+                name: options.normalize(name).into_owned(),
+                parent: None,
+                selected: true,
+            });
+        }
+    }
+    if let Some(delimiter) = options
+        .custom_dialect
+        .as_ref()
+        .and_then(|dialect| dialect.delimiter(line.text))
+    {
+        let before = &line.text[..delimiter.start];
+        let key = before.trim_matches([' ', '\t']);
+        let key_start = before.len() - before.trim_start_matches([' ', '\t']).len();
+        let after = &line.text[delimiter.end..];
+        let value = after.trim_matches([' ', '\t']);
+        let value_start = delimiter.end + after.len() - after.trim_start_matches([' ', '\t']).len();
+        return Ok(Token::Property {
+            key_span: key_start..key_start + key.len(),
+            key: options.normalize_key(key.to_string()),
+            value_span: value_start..value_start + value.len(),
+            value: options.unescape(value.to_string()),
+        });
+    }
+    let pair = Ini::parse(Rule::line, line.text)
+        .map_err(|err| relocate(err, content, line.start))?
+        .next()
+        .and_then(|line| line.into_inner().next());
+    let Some(pair) = pair else {
+        return Ok(Token::Blank);
+    };
+    match pair.as_rule() {
+        Rule::EOI => Ok(Token::Blank),
+        Rule::section => {
+            let mut pairs = pair.into_inner();
+            let name = pairs.next().ok_or(InIParseError::Unreachable)?;
+            let name_span = span(name.clone());
+            let mut name = name.as_str().to_string();
+            let (mut parent, mut selected) = (None, true);
+            for pair in pairs {
+                match pair.as_rule() {
+                    Rule::subsection => name = subsection_name(&name, &unquote(pair.as_str())),
+                    Rule::parent => {
+                        parent = Some(options.normalize(inner(pair, 0)?.as_str()).into_owned())
+                    }
+                    _ if options.conditions.is_empty() => {
+                        return Err(error_at(
+                            content,
+                            line.start + pair.as_span().start(),
+                            "expected `]`; conditional sections are disabled",
+                        ));
+                    }
+                    _ => {
+                        selected = pair.into_inner().all(|condition| {
+                            let mut names = condition.into_inner().map(|name| name.as_str());
+                            matches!(
+                                (names.next(), names.next()),
+                                (Some(key), Some(value)) if options.holds(key, value)
+                            )
+                        });
+                    }
+                }
+            }
+            Ok(Token::Section {
+                name_span,
+                name: options.normalize(&name).into_owned(),
+                parent,
+                selected,
+            })
+        }
+        Rule::include | Rule::include_dir => {
+            let dir = pair.as_rule() == Rule::include_dir;
+            if !options.includes {
+                let directive = if dir { "!includedir" } else { "!include" };
+                return Err(error_at(
+                    content,
+                    line.start + pair.as_span().start(),
+                    &format!("unexpected `{directive}`; includes are disabled"),
+                ));
+            }
+            let path = inner(pair, 0)?;
+            Ok(Token::Include {
+                path_span: span(path.clone()),
+                path: path.as_str().trim_end_matches([' ', '\t']).to_string(),
+                dir,
+            })
+        }
+        Rule::property => {
+            let key_pair = inner(pair.clone(), 0)?;
+            let quoted_key = key_pair.as_rule() == Rule::quoted_key;
+            let (mut key_span, mut key) = read_key(content, line, key_pair, options)?;
+            let delimiter = inner(pair.clone(), 1)?;
+            if options.trim == TrimPolicy::Verbatim && !quoted_key {
+                key_span = 0..delimiter.as_span().start();
+                key = line.text[key_span.clone()].to_string();
+            }
+            if delimiter.as_str() == ":" && !options.colon_delimiter {
+                return Err(error_at(
+                    content,
+                    line.start + delimiter.as_span().start(),
+                    "expected `=`; `:` delimiters are disabled",
+                ));
+            }
+            let mut raw = span(inner(pair, 2)?);
+            if options.trim != TrimPolicy::Both {
+                raw.start = delimiter.as_span().end();
+            }
+            let text = &line.text[raw.clone()];
+            let (len, value) = if text.contains('\n') {
+                let mut values = Vec::new();
+                for (i, joined) in join_lines(text, options).iter().enumerate() {
+                    // The first line is trimmed like any value, the others lose their indentation.
+                    let piece = match i {
+                        0 => &joined.text[..],
+                        _ => joined.text.trim_start_matches([' ', '\t']),
+                    };
+                    if let Some((offset, message)) = strict_violation(piece, options) {
+                        let offset = joined.raw_offset(joined.text.len() - piece.len() + offset);
+                        return Err(error_at(content, line.start + raw.start + offset, &message));
+                    }
+                    values.push(read_value(piece, options).1);
+                }
+                (text.trim_end_matches([' ', '\t']).len(), values.join("\n"))
+            } else {
+                if let Some((offset, message)) = strict_violation(text, options) {
+                    return Err(error_at(content, line.start + raw.start + offset, &message));
+                }
+                read_value(text, options)
+            };
+            if len == 0 && !options.empty_values {
+                return Err(error_at(
+                    content,
+                    line.start + raw.start,
+                    "expected a value; empty values are disabled",
+                ));
+            }
+            Ok(Token::Property {
+                key_span,
+                key: options.normalize_key(key),
+                value_span: raw.start..raw.start + len,
+                value: options.unescape(value),
+            })
+        }
+        Rule::flag => {
+            let (key_span, key) = read_key(content, line, inner(pair, 0)?, options)?;
+            let value = match options.valueless_keys {
+                ValuelessKeys::Reject => {
+                    return Err(error_at(
+                        content,
+                        line.start + key_span.end,
+                        "expected `=` after the key; valueless keys are disabled",
+                    ))
+                }
+                ValuelessKeys::Empty => String::new(),
+                ValuelessKeys::True => "true".to_string(),
+            };
+            Ok(Token::Property {
+                value_span: key_span.end..key_span.end,
+                key_span,
+                key: options.normalize_key(key),
+                value,
+            })
+        }
+        _ => Err(InIParseError::Unreachable),
+    }
+}
+
+/// Reads a key, removing the quotes and escapes of a quoted key and collapsing the spaces of
+/// a spaced key.
+fn read_key(
+    content: &str,
+    line: &RawLine,
+    pair: Pair<'_, Rule>,
+    options: &ParserOptions,
+) -> INIParserResult<(Range<usize>, String)> {
+    let text = pair.as_str();
+    let key = match pair.as_rule() {
+        Rule::quoted_key => unquote(text),
+        Rule::spaced_key if !options.spaced_keys => {
+            let space = text.find([' ', '\t']).unwrap_or(0);
+            return Err(error_at(
+                content,
+                line.start + pair.as_span().start() + space,
+                "expected a delimiter; keys with spaces are disabled",
+            ));
+        }
+        Rule::spaced_key => text.split_whitespace().collect::<Vec<_>>().join(" "),
+        _ => text.to_string(),
+    };
+    Ok((span(pair), key))
+}
+
+/// Strips the double quotes around a quoted key or subsection and resolves its `\"` and `\\`
+/// escapes.
+pub(crate) fn unquote(text: &str) -> String {
+    let mut unquoted = String::with_capacity(text.len());
+    let mut chars = text[1..text.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('"' | '\\'))) => {
+                chars.next();
+                unquoted.push(next);
+            }
+            _ => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+/// Reads the value of a property from the rest of its line.
+/// Returns the length of the value as written together with the value itself.
+fn read_value(raw: &str, options: &ParserOptions) -> (usize, String) {
+    if options.quoted_values {
+        if let Some(quoted) = read_quoted(raw, options) {
+            return quoted;
+        }
+    }
+    let trim = options.trim == TrimPolicy::Both;
+    if !options.inline_comments {
+        let value = if trim {
+            raw.trim_end_matches([' ', '\t'])
+        } else {
+            raw
+        };
+        return (value.len(), value.to_string());
+    }
+    let mut value = String::new();
+    let mut len = 0;
+    let mut chars = raw.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let rest = &raw[i..];
+        if c == '\\' {
+            if let Some(&(_, next)) = chars
+                .peek()
+                .filter(|(_, next)| options.starts_comment(*next))
+            {
+                chars.next();
+                value.push(next);
+                len = i + 1 + next.len_utf8();
+                continue;
+            }
+        } else if (i == 0 || raw[..i].ends_with([' ', '\t'])) && options.is_comment(rest) {
+            break;
+        }
+        value.push(c);
+        if !trim || !matches!(c, ' ' | '\t') {
+            len = i + c.len_utf8();
+        }
+    }
+    if trim {
+        value.truncate(value.trim_end_matches([' ', '\t']).len());
+    }
+    (len, value)
+}
+
+/// Reads a value wrapped in double or single quotes, keeping everything between them verbatim.
+/// Returns `None` if the value is not quoted or if anything but whitespace or an inline comment
+/// follows the closing quote, in which case the value is read as unquoted text.
+fn read_quoted(raw: &str, options: &ParserOptions) -> Option<(usize, String)> {
+    let quote = raw.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let body = &raw[1..];
+    let end = closing_quote(body, quote)?;
+    let len = end + 2;
+    let rest = raw[len..].trim_start_matches([' ', '\t']);
+    let comment = options.inline_comments && options.is_comment(rest);
+    if !rest.is_empty() && !comment {
+        return None;
+    }
+    let body = &body[..end];
+    if quote == '"' && options.escapes {
+        return Some((len, unescape(body)));
+    }
+    Some((len, body.to_string()))
+}
+
+/// Returns the byte offset in `body` of the quote closing a value opened with `quote`, skipping
+/// the quotes escaped with a backslash in double-quoted values.
+fn closing_quote(body: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    body.char_indices().find_map(|(i, c)| {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i);
+        }
+        None
+    })
+}
+
+/// Finds what `ParserOptions::strict` rejects in the text of a value: anything but an inline
+/// comment after the closing quote of a quoted value, and unknown escape sequences between
+/// double quotes. Returns the offset of the problem in `raw` and a description of it.
+fn strict_violation(raw: &str, options: &ParserOptions) -> Option<(usize, String)> {
+    if !options.strict || !options.quoted_values {
+        return None;
+    }
+    let quote = raw.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let body = &raw[1..];
+    let end = closing_quote(body, quote)?;
+    let rest = raw[end + 2..].trim_start_matches([' ', '\t']);
+    let comment = options.inline_comments && options.is_comment(rest);
+    if !rest.is_empty() && !comment {
+        let message = "unexpected text after the closing quote".to_string();
+        return Some((raw.len() - rest.len(), message));
+    }
+    if quote != '"' || !options.escapes {
+        return None;
+    }
+    let body = &body[..end];
+    let mut at = 0;
+    while let Some(found) = body[at..].find('\\') {
+        let start = at + found;
+        let c = body[start + 1..].chars().next()?;
+        at = start + 1 + c.len_utf8();
+        let known = match c {
+            'u' | 'x' => match unicode_escape(c, &body[at..]) {
+                Some((_, len)) => {
+                    at += len;
+                    true
+                }
+                None => false,
+            },
+            c => escaped(c).is_some(),
+        };
+        if !known {
+            return Some((1 + start, format!("unknown escape sequence `\\{c}`")));
+        }
+    }
+    None
+}
+
+/// Returns the character a single-character escape sequence such as `\n` stands for.
+fn escaped(c: char) -> Option<char> {
+    match c {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        '\\' | '"' | '\'' | ';' | '#' => Some(c),
+        _ => None,
+    }
+}
+
+/// Replaces the escape sequences of a double-quoted value with the characters they stand for.
+/// Unknown sequences are kept as they are.
+fn unescape(body: &str) -> String {
+    let mut value = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(c @ ('u' | 'x')) => match unicode_escape(c, chars.as_str()) {
+                Some((decoded, len)) => {
+                    value.push(decoded);
+                    chars = chars.as_str()[len..].chars();
+                }
+                None => {
+                    value.push('\\');
+                    value.push(c);
+                }
+            },
+            Some(c) => match escaped(c) {
+                Some(escaped) => value.push(escaped),
+                None => {
+                    value.push('\\');
+                    value.push(c);
+                }
+            },
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+/// Decodes the character of a `\uXXXX` or `\x{...}` escape from the text following `\u` or
+/// `\x`, joining UTF-16 surrogate pairs written as two `\u` escapes.
+/// Returns the character and the number of bytes of `rest` it used.
+pub(crate) fn unicode_escape(kind: char, rest: &str) -> Option<(char, usize)> {
+    let hex = |digits: &str| u32::from_str_radix(digits, 16).ok();
+    if kind == 'x' {
+        let end = rest.strip_prefix('{')?.find('}')?;
+        let digits = &rest[1..end + 1];
+        if digits.is_empty() || digits.len() > 6 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        return Some((char::from_u32(hex(digits)?)?, end + 2));
+    }
+    let unit = |digits: Option<&str>| {
+        digits
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(hex)
+    };
+    let high = unit(rest.get(..4))?;
+    if let Some(c) = char::from_u32(high) {
+        return Some((c, 4));
+    }
+    let low = unit(
+        rest.get(4..6)
+            .filter(|escape| *escape == "\\u")
+            .and(rest.get(6..10)),
+    )?;
+    if !(0xD800..0xDC00).contains(&high) || !(0xDC00..0xE000).contains(&low) {
+        return None;
+    }
+    let c = char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))?;
+    Some((c, 10))
+}
+
+/// Returns the `n`-th inner pair of a rule.
+fn inner(pair: Pair<'_, Rule>, n: usize) -> INIParserResult<Pair<'_, Rule>> {
+    pair.into_inner().nth(n).ok_or(InIParseError::Finished)
+}
+
+fn span(pair: Pair<'_, Rule>) -> Range<usize> {
+    let span = pair.as_span();
+    span.start()..span.end()
+}
+
+/// Builds a syntax error pointing at a byte offset of the whole input.
+pub(crate) fn error_at(content: &str, offset: usize, message: &str) -> InIParseError {
+    kind_error_at(content, offset, ParseErrorKind::Syntax, message)
+}
+
+/// Builds an error of the given kind pointing at a byte offset of the whole input.
+pub(crate) fn kind_error_at(
+    content: &str,
+    offset: usize,
+    kind: ParseErrorKind,
+    message: &str,
+) -> InIParseError {
+    let mut err = ParseError::new(content, offset, message);
+    err.kind = kind;
+    InIParseError::UnsuccessfulParse(err)
+}
+
+/// Turns an error raised while parsing a single line into an error about the whole input.
+fn relocate(err: Error<Rule>, content: &str, line_start: usize) -> InIParseError {
+    let offset = match err.location {
+        InputLocation::Pos(pos) => pos,
+        InputLocation::Span((start, _)) => start,
+    };
+    error_at(content, line_start + offset, &err.variant.message())
+}
@@ -1,30 +1,94 @@
-///! An INI parser struct.
-/// This struct can be used to parse an INI-formatted string or file and convert it into a
-/// HashMap of HashMaps where each inner HashMap contains key-value pairs of properties in a section.
-/// Examples
-///
-/// ```rust
-/// use std::collections::HashMap;
-/// use dotini::INIParser;
-/// use ini_parser::INIParser;
-/// let content = r#"
-/// [user]
-/// name = John Doe
-/// email = johndoe@example.com
-/// "#;
-///
-/// let parser = INIParser::from_string(content).unwrap();
-/// let output: HashMap<String, HashMap<String, String>> = parser.into_inner();
-/// assert_eq!(output["user"]["name"], "John Doe");
-/// assert_eq!(output["user"]["email"], "johndoe@example.com");
-///
+//! An INI parser struct.
+//! This struct can be used to parse an INI-formatted string or file and convert it into a
+//! map of maps where each inner map contains key-value pairs of properties in a section.
+//! Sections and properties keep the order in which they appear in the input.
+//! Examples
+//!
+//! ```rust
+//! use dotini::{INIParser, Sections};
+//!
+//! let content = r#"
+//! [user]
+//! name = John
+//! home = /home/john
+//! "#;
+//!
+//! let parser = INIParser::from_string(content).unwrap();
+//! let output: Sections = parser.into_inner();
+//! assert_eq!(output["user"]["name"], "John");
+//! assert_eq!(output["user"]["home"], "/home/john");
+//! ```
 extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
-use pest::Parser;
+mod compare;
+#[cfg(feature = "serde")]
+mod de;
+mod decode;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod dialect;
+mod document;
+mod dotenv;
+mod error;
+mod from_ini;
+mod interpolate;
+mod iter;
+mod java;
+mod lexer;
+mod options;
+mod reg;
+mod section;
+#[cfg(feature = "serde")]
+mod ser;
+mod span;
+mod tree;
+mod writer;
+
+pub use compare::EqOptions;
+#[cfg(feature = "serde")]
+pub use de::from_str;
+pub use dialect::IniDialect;
+pub use document::IniDocument;
+#[cfg(feature = "derive")]
+pub use dotini_derive::FromIni;
+pub use error::{ParseError, ParseErrorKind, ValueError, Warning, WarningKind};
+pub use from_ini::FromIni;
+pub use iter::{IntoIter, Iter};
+pub use options::{
+    Dialect, DuplicateKeyPolicy, DuplicateSectionPolicy, Interpolation, Normalization, Order,
+    OrphanPolicy, ParserOptions, TrimPolicy, ValuelessKeys,
+};
+pub use reg::{RegFile, RegKey, RegValue};
+pub use section::{Section, SectionMut};
+#[cfg(feature = "serde")]
+pub use ser::{to_string, to_string_with_options};
+pub use span::Span;
+pub use tree::SectionTree;
+pub use writer::{Encoding, IniWriter, LineEnding, QuotePolicy, WriteOptions};
+
+use indexmap::map::Entry;
+use indexmap::IndexMap;
+use lexer::Token;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fs;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::Read;
+use std::ops::{Index, IndexMut, Range};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Name of the section holding the properties that appear before the first section header,
+/// unless another one is set with `ParserOptions::default_section`.
+pub const DEFAULT_SECTION: &str = "untagged";
+
+/// The properties of a single section, in the order they were added.
+pub type Properties = IndexMap<String, String>;
+
+/// The sections of a config, in the order they were added.
+pub type Sections = IndexMap<String, Properties>;
 
 /// Generic Result type for dotini.
 pub type INIParserResult<T> = Result<T, InIParseError>;
@@ -33,107 +97,2505 @@ pub type INIParserResult<T> = Result<T, InIParseError>;
 #[derive(Debug)]
 pub enum InIParseError {
     FileReadError(String),
-    UnsuccessfulParse(String),
+    FileWriteError(String),
+    SerializationError(String),
+    /// A config does not match the type it is deserialized into, see `from_str`.
+    DeserializationError(String),
+    UnsuccessfulParse(ParseError),
+    MissingKey(String),
+    ConversionError(ValueError),
+    InvalidBool(ValueError),
+    InvalidNumber(ValueError),
+    InterpolationError(String),
+    /// An interpolated value grows past `ParserOptions::max_interpolated_length`.
+    InterpolationTooLarge {
+        property: String,
+        limit: usize,
+    },
+    /// The input is larger than `ParserOptions::max_input_size`.
+    InputTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    /// A line, with its continuations, is longer than `ParserOptions::max_line_length`.
+    LineTooLong {
+        line: usize,
+        length: usize,
+        limit: usize,
+    },
+    /// The input declares more sections than `ParserOptions::max_sections`.
+    TooManySections {
+        limit: usize,
+    },
+    /// A section holds more keys than `ParserOptions::max_keys_per_section`.
+    TooManyKeys {
+        section: String,
+        limit: usize,
+    },
+    /// Every problem found by `INIParser::from_string_with_all_errors`.
+    Multiple(Vec<InIParseError>),
     Finished,
     Unreachable,
 }
 
+impl InIParseError {
+    /// Returns `true` for the errors raised by the limits of `ParserOptions`, which end a
+    /// parse even with `ParserOptions::lenient`.
+    fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            Self::InputTooLarge { .. }
+                | Self::LineTooLong { .. }
+                | Self::TooManySections { .. }
+                | Self::TooManyKeys { .. }
+        )
+    }
+}
+
 /// Ini is the main parser that does the job for us.
 /// takes some set of rules from ini.pest file.
 #[derive(Parser)]
 #[grammar = "ini.pest"]
 pub struct Ini;
 
-/// The INIParser struct is used to parse INI configuration files into a HashMap data structure for easy access to configuration values.
+/// The INIParser struct is used to parse INI configuration files into a map data structure for easy access to configuration values.
+/// Sections and properties keep the order in which they appear in the input.
 /// To use the INIParser, we only need to create a new instance of the struct using either: `INIParser::from_string` or `INIParser::from_file`. the configuration values are stored in the output field of the struct
 #[derive(Debug)]
 pub struct INIParser {
-    pub output: HashMap<String, HashMap<String, String>>,
+    pub output: Sections,
+    options: ParserOptions,
+    line_ending: LineEnding,
+    /// Every value of the keys repeated under `DuplicateKeyPolicy::CollectAll`; `output`
+    /// holds the last one.
+    multi_values: HashMap<String, HashMap<String, Vec<String>>>,
+    /// The blocks of sections repeated under `DuplicateSectionPolicy::Separate`, after the
+    /// first one held in `output`.
+    extra_sections: HashMap<String, Vec<Properties>>,
+    /// The parent of each section declared as `[child : parent]`, by child name.
+    parents: HashMap<String, String>,
+    /// The errors of the lines skipped under `ParserOptions::lenient`.
+    errors: Vec<InIParseError>,
+    warnings: Vec<Warning>,
+    /// Where the sections and properties were read.
+    spans: span::Spans,
+    /// The names read so far under `ParserOptions::case_insensitive`, only kept while reading.
+    folded: section::FoldedNames,
+}
+
+/// Where `INIParser::read` is within the text it reads.
+struct ReadState<'a> {
+    options: &'a ParserOptions,
+    /// The section the next properties belong to.
+    section: String,
+    /// Set while inside a conditional section whose conditions do not hold.
+    skipping: bool,
+    /// The index of the repeated block of the current section being read, if any.
+    block: Option<usize>,
+    /// Set once a section header has been read.
+    headed: bool,
+    /// The sections whose headers were read so far, each with the warning to give if it stays
+    /// empty.
+    headers: Vec<(String, Warning)>,
+    /// The number of the line being read, starting at 1.
+    line: usize,
+    /// The offset up to which lines were counted.
+    counted: usize,
+}
+
+impl ReadState<'_> {
+    /// Returns the span of a byte range of the line being read, which starts at `line_start`.
+    fn span(&self, path: Option<&Path>, line_start: usize, range: Range<usize>) -> Span {
+        Span {
+            line: self.line,
+            range: line_start + range.start..line_start + range.end,
+            file: path.map(Path::to_path_buf),
+        }
+    }
 }
 
 impl INIParser {
+    /**
+     * Creates a new INIParser struct from an INI-formatted string.
+     *
+     * # Arguments
+     * * `content` - An INI-formatted string to parse.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the parsed `INIParser` struct, or an `INIParseError`
+     * if there is an issue parsing the content.
+     *
+     * A property is split at its first delimiter, so values may contain `=` and `:`:
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[db]\ndsn = host=localhost;port=5432\ntoken = c2VjcmV0==\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("db", "dsn"), Some("host=localhost;port=5432"));
+     * assert_eq!(parser.get("db", "token"), Some("c2VjcmV0=="));
+     * ```
+     */
     pub fn from_string(content: &str) -> INIParserResult<Self> {
-        Self::parse(content)
+        Self::parse(content, ParserOptions::default())
+    }
+
+    /**
+     * Creates a new INIParser struct from an INI-formatted string using the given options.
+     *
+     * # Arguments
+     * * `content` - An INI-formatted string to parse.
+     * * `options` - A `ParserOptions` controlling parsing; the parser keeps them for later use.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the parsed `INIParser` struct, or an `INIParseError`
+     * if there is an issue parsing the content.
+     */
+    pub fn from_string_with_options(
+        content: &str,
+        options: ParserOptions,
+    ) -> INIParserResult<Self> {
+        Self::parse(content, options)
+    }
+
+    /**
+     * Creates a new INIParser struct from an INI-formatted string, reading past the lines that
+     * cannot be read as `ParserOptions::lenient` does, but failing with every problem of the
+     * content at once rather than the first one, so a file can be fixed in one go.
+     *
+     * # Arguments
+     * * `content` - An INI-formatted string to parse.
+     * * `options` - A `ParserOptions` controlling parsing; the parser keeps them for later use.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the parsed `INIParser` struct if the content has
+     * no problems, or `InIParseError::Multiple` holding all of them in the order they appear.
+     *
+     * ```rust
+     * use dotini::{INIParser, InIParseError, ParserOptions};
+     *
+     * let content = "[server]\nport 8080\nhost = localhost\n[broken\n";
+     * let err = INIParser::from_string_with_all_errors(content, ParserOptions::new()).unwrap_err();
+     * assert!(err.to_string().starts_with("2 errors:\n  parse error at 2:5: "));
+     * let InIParseError::Multiple(errors) = err else { panic!() };
+     * assert_eq!(errors.len(), 2);
+     * assert!(matches!(&errors[1], InIParseError::UnsuccessfulParse(err) if err.line == 4));
+     *
+     * let parser = INIParser::from_string_with_all_errors("[server]\nport = 8080\n", ParserOptions::new()).unwrap();
+     * assert_eq!(parser.get("server", "port"), Some("8080"));
+     * ```
+     */
+    pub fn from_string_with_all_errors(
+        content: &str,
+        options: ParserOptions,
+    ) -> INIParserResult<Self> {
+        let mut parser = Self::from_map(Sections::new());
+        parser.options = options.lenient(true);
+        let read = parser.read_all(content, None);
+        let mut errors = std::mem::take(&mut parser.errors);
+        errors.extend(read.err());
+        match errors.is_empty() {
+            true => Ok(parser),
+            false => Err(InIParseError::Multiple(errors)),
+        }
+    }
+
+    /**
+     * Creates a new INIParser struct from an already built map of sections.
+     *
+     * # Arguments
+     * * `output` - A map of section names to maps of key-value pairs.
+     *
+     * # Returns
+     * Returns an `INIParser` holding the given data, so it can be used with the same getters
+     * as a parsed config.
+     *
+     * ```rust
+     * use dotini::{INIParser, Properties, Sections};
+     *
+     * let mut server = Properties::new();
+     * server.insert("port".to_string(), "8080".to_string());
+     * let parser = INIParser::from_map(Sections::from([("server".to_string(), server)]));
+     * assert_eq!(parser.get_as::<u16>("server", "port").unwrap(), 8080);
+     * ```
+     */
+    pub fn from_map(output: Sections) -> Self {
+        Self {
+            output,
+            options: ParserOptions::default(),
+            line_ending: LineEnding::default(),
+            multi_values: HashMap::new(),
+            extra_sections: HashMap::new(),
+            parents: HashMap::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            spans: span::Spans::default(),
+            folded: section::FoldedNames::default(),
+        }
     }
 
     /**
-     * Creates a new INIParser struct from an INI file.
+     * Creates a new INIParser struct from an INI file. UTF-8 files may start with a byte order
+     * mark, and UTF-16 files, with or without one, are decoded as well.
      *
      * # Arguments
      * * `path` - A string containing the path to the INI file to parse.
      *
      * # Returns
      * Returns an `INIParserResult` containing the parsed `INIParser` struct, or an `INIParseError`
-     * if there is an issue reading or parsing the file.
+     * if there is an issue reading, decoding or parsing the file.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let dir = tempfile::tempdir().unwrap();
+     * let path = dir.path().join("setup.ini");
+     * let mut bytes = vec![0xFF, 0xFE];
+     * bytes.extend("[Setup]\r\nName=Caf\u{e9}\r\n".encode_utf16().flat_map(u16::to_le_bytes));
+     * std::fs::write(&path, bytes).unwrap();
+     * let parser = INIParser::from_file(path.to_str().unwrap()).unwrap();
+     * assert_eq!(parser.get("Setup", "Name"), Some("Caf\u{e9}"));
+     *
+     * std::fs::write(&path, "\u{feff}[Setup]\nName=plain\n").unwrap();
+     * let parser = INIParser::from_file(path.to_str().unwrap()).unwrap();
+     * assert_eq!(parser.get("Setup", "Name"), Some("plain"));
+     * ```
      */
     pub fn from_file(path: &str) -> INIParserResult<Self> {
-        let content = fs::read_to_string(path)
-            .map_err(|err| InIParseError::FileReadError(err.to_string()))?;
+        Self::from_file_with_options(path, ParserOptions::default())
+    }
+
+    /**
+     * Creates a new INIParser struct from an INI file using the given options.
+     *
+     * # Arguments
+     * * `path` - A string containing the path to the INI file to parse.
+     * * `options` - A `ParserOptions` controlling parsing; the parser keeps them for later use.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the parsed `INIParser` struct, or an `INIParseError`
+     * if there is an issue reading or parsing the file.
+     */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(options))
+    )]
+    pub fn from_file_with_options(path: &str, options: ParserOptions) -> INIParserResult<Self> {
+        let bytes = fs::read(path).map_err(|err| InIParseError::FileReadError(err.to_string()))?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(bytes = bytes.len(), "read file");
+        let content = decode::decode(bytes)?;
+
+        Self::parse_from(&content, Some(Path::new(path)), options)
+    }
+
+    /**
+     * Creates a new INIParser struct from an INI file while holding a shared advisory lock on
+     * it, so concurrent writers using `write_to_file_locked` cannot change it mid-read.
+     *
+     * # Arguments
+     * * `path` - A string containing the path to the INI file to parse.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the parsed `INIParser` struct, or an `INIParseError`
+     * if there is an issue locking, reading or parsing the file.
+     */
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub fn from_file_locked(path: &str) -> INIParserResult<Self> {
+        let read = || -> std::io::Result<Vec<u8>> {
+            let mut file = File::open(path)?;
+            file.lock_shared()?;
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            Ok(bytes)
+        };
+        let bytes = read().map_err(|err| InIParseError::FileReadError(err.to_string()))?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(bytes = bytes.len(), "read file");
+        let content = decode::decode(bytes)?;
 
-        Self::parse(&content)
+        Self::parse_from(&content, Some(Path::new(path)), ParserOptions::default())
     }
 
     /**
-     * Returns the inner HashMap of the `INIParser` struct.
+     * Returns the inner map of the `INIParser` struct.
      *
      * # Returns
-     * Returns a `HashMap` where each key is a section in the INI file and the corresponding value
-     * is another `HashMap` containing key-value pairs of properties in that section.
+     * Returns a `Sections` map where each key is a section in the INI file and the corresponding
+     * value is a `Properties` map containing key-value pairs of properties in that section.
      */
-    pub fn into_inner(self) -> HashMap<String, HashMap<String, String>> {
+    pub fn into_inner(self) -> Sections {
         self.output
     }
 
     /**
-     * Parses an INI-formatted string and returns an `INIParser` struct containing the parsed content.
+     * Borrows the inner map of the `INIParser` struct without consuming it.
+     *
+     * # Returns
+     * Returns a reference to the `Sections` map, each section holding a `Properties` map of
+     * key-value pairs.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[server]\nport = 8080\n").unwrap();
+     * assert_eq!(parser.inner()["server"]["port"], "8080");
+     * assert_eq!(parser.get("server", "port"), Some("8080"));
+     * ```
+     */
+    pub fn inner(&self) -> &Sections {
+        &self.output
+    }
+
+    /**
+     * Mutably borrows the inner map of the `INIParser` struct without consuming it.
+     *
+     * # Returns
+     * Returns a mutable reference to the `Sections` map, each section holding a `Properties`
+     * map of key-value pairs.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[server]\nport = 8080\n").unwrap();
+     * let server = parser.inner_mut().get_mut("server").unwrap();
+     * server.insert("port".to_string(), "9090".to_string());
+     * assert_eq!(parser.get("server", "port"), Some("9090"));
+     * ```
+     */
+    pub fn inner_mut(&mut self) -> &mut Sections {
+        &mut self.output
+    }
+
+    /**
+     * Returns the value of a property without consuming the parser.
      *
      * # Arguments
-     * * `content` - An INI-formatted string to parse.
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
      *
      * # Returns
-     * Returns an `INIParserResult` containing the parsed `INIParser` struct, or an `INIParseError`
-     * if there is an issue parsing the content.
+     * Returns `Some` with the property value, or `None` if either the section or the key
+     * does not exist.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[server]\nport=8080\n").unwrap();
+     * assert_eq!(parser.get("server", "port"), Some("8080"));
+     * assert_eq!(parser.get("server", "host"), None);
+     * ```
      */
-    fn parse(content: &str) -> INIParserResult<Self> {
-        let ini = Ini::parse(Rule::file, content)
-            .map_err(|err| InIParseError::UnsuccessfulParse(err.to_string()))?
-            .next()
-            .ok_or(InIParseError::UnsuccessfulParse(
-                "Unsuccessful parse".to_string(),
-            ))?;
-        let mut output: HashMap<String, HashMap<String, String>> = HashMap::new();
-        let mut current_section = "untagged".to_string();
-
-        for line in ini.into_inner() {
-            match line.as_rule() {
-                Rule::section => {
-                    current_section = line.into_inner()
-                        .next()
-                        .ok_or(InIParseError::Finished)?
-                        .as_str()
-                        .to_string();
-                }
-                Rule::property => {
-                    let mut prop = line.into_inner();
-                    let name = prop
-                        .next()
-                        .ok_or(InIParseError::Finished)?
-                        .as_str()
-                        .to_string();
-                    let val = prop
-                        .next()
-                        .ok_or(InIParseError::Finished)?
-                        .as_str()
-                        .to_string();
-
-                    output.entry(current_section.to_string())
-                          .or_default()
-                          .insert(name, val);
-                }
-                Rule::EOI => (),
-                _ => Err(InIParseError::Unreachable),
-            };
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.get_written(section, key).or_else(|| {
+            let (section, key) = self.options.migration(section, key)?.to.as_ref()?;
+            self.get_written(section, key)
+        })
+    }
+
+    /// Returns the value of a property, without following `ParserOptions::migrate_key`.
+    fn get_written(&self, section: &str, key: &str) -> Option<&str> {
+        let view = self.section(section)?;
+        view.get(key)
+            .or_else(|| self.inherited(view.name(), key))
+            .or_else(|| self.fallback(key))
+    }
+
+    /**
+     * Returns every value of a property, in the order they appear in the input.
+     * Keys are only given several values when parsing with `DuplicateKeyPolicy::CollectAll`;
+     * changing a property afterwards replaces its values with the new one.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns a slice of the values, which is empty if the property does not exist.
+     *
+     * ```rust
+     * use dotini::{DuplicateKeyPolicy, INIParser, ParserOptions};
+     *
+     * let content = "[ldap]\nuri = ldap://a.example.com\nuri = ldap://b.example.com\n";
+     * let options = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::CollectAll);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get_all("ldap", "uri"), ["ldap://a.example.com", "ldap://b.example.com"]);
+     * assert_eq!(parser.get("ldap", "uri"), Some("ldap://b.example.com"));
+     * assert!(parser.get_all("ldap", "base").is_empty());
+     * assert_eq!(
+     *     parser.to_ini_string().unwrap(),
+     *     "[ldap]\nuri = ldap://a.example.com\nuri = ldap://b.example.com\n"
+     * );
+     * ```
+     */
+    pub fn get_all(&self, section: &str, key: &str) -> &[String] {
+        let Some((section, properties)) = self.lookup(section) else {
+            return &[];
+        };
+        let key = self.options.normalize(key);
+        let Some((key, value)) = section::find_key(properties, &key, self.options.case_insensitive)
+            .and_then(|index| properties.get_index(index))
+        else {
+            return &[];
+        };
+        match self
+            .multi_values
+            .get(section)
+            .and_then(|values| values.get(key))
+        {
+            // Values changed through `inner_mut` or a section view no longer match the list.
+            Some(values) if values.last() == Some(value) => values,
+            // An empty assignment under `ParserOptions::empty_resets` leaves an empty list.
+            Some(values) if values.is_empty() && value.is_empty() => &[],
+            _ => std::slice::from_ref(value),
+        }
+    }
+
+    /**
+     * Returns the values of a php-style list, written as `name[] = value` lines.
+     *
+     * # Arguments
+     * * `section` - The name of the section the list belongs to.
+     * * `name` - The name of the list, without the brackets.
+     *
+     * # Returns
+     * Returns a slice of the values in the order they appear, which is empty if the list does
+     * not exist.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[PHP]\nextension[] = curl\nextension[] = mbstring\nlimit[memory] = 128M\nlimit[time] = 30\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get_array("PHP", "extension"), ["curl", "mbstring"]);
+     * assert_eq!(parser.get_array_map("PHP", "limit"), [("memory", "128M"), ("time", "30")]);
+     * assert_eq!(parser.get("PHP", "limit[time]"), Some("30"));
+     * assert_eq!(parser.to_ini_string().unwrap(), content);
+     * ```
+     */
+    pub fn get_array(&self, section: &str, name: &str) -> &[String] {
+        self.get_all(section, &format!("{name}[]"))
+    }
+
+    /**
+     * Returns the value of a localized key, such as `Name[fr_FR]` in a Desktop Entry file,
+     * following the fallback order of the XDG Desktop Entry specification. For a locale of the
+     * form `lang_COUNTRY.ENCODING@MODIFIER` the encoding is ignored and the keys tried are
+     * `key[lang_COUNTRY@MODIFIER]`, `key[lang_COUNTRY]`, `key[lang@MODIFIER]`, `key[lang]`
+     * and finally the unlocalized `key`.
+     *
+     * # Arguments
+     * * `section` - The name of the section the key belongs to.
+     * * `key` - The name of the key, without a locale.
+     * * `locale` - The locale to look up, e.g. `sr_RS@latin` or `fr_FR.UTF-8`.
+     *
+     * # Returns
+     * Returns `Some` with the best matching value, or `None` if neither a localized nor the
+     * unlocalized key exists.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[Desktop Entry]\nName=Files\nName[fr]=Fichiers\nName[sr@latin]=Datoteke\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get_localized("Desktop Entry", "Name", "fr_CA.UTF-8"), Some("Fichiers"));
+     * assert_eq!(parser.get_localized("Desktop Entry", "Name", "sr_RS@latin"), Some("Datoteke"));
+     * assert_eq!(parser.get_localized("Desktop Entry", "Name", "de_DE"), Some("Files"));
+     * ```
+     */
+    pub fn get_localized(&self, section: &str, key: &str, locale: &str) -> Option<&str> {
+        let (base, modifier) = match locale.split_once('@') {
+            Some((base, modifier)) => (base, Some(modifier)),
+            None => (locale, None),
+        };
+        let base = base.split_once('.').map_or(base, |(base, _)| base);
+        let (lang, country) = match base.split_once('_') {
+            Some((lang, country)) => (lang, Some(country)),
+            None => (base, None),
+        };
+        let mut candidates = Vec::with_capacity(4);
+        if let (Some(country), Some(modifier)) = (country, modifier) {
+            candidates.push(format!("{lang}_{country}@{modifier}"));
         }
-        Ok(Self { output })
+        if let Some(country) = country {
+            candidates.push(format!("{lang}_{country}"));
+        }
+        if let Some(modifier) = modifier {
+            candidates.push(format!("{lang}@{modifier}"));
+        }
+        candidates.push(lang.to_string());
+        candidates
+            .iter()
+            .find_map(|locale| self.get(section, &format!("{key}[{locale}]")))
+            .or_else(|| self.get(section, key))
+    }
+
+    /**
+     * Returns the entries of a php-style map, written as `name[key] = value` lines.
+     *
+     * # Arguments
+     * * `section` - The name of the section the map belongs to.
+     * * `name` - The name of the map, without the brackets.
+     *
+     * # Returns
+     * Returns a `Vec` of the `(key, value)` entries in the order they appear, which is empty
+     * if the map does not exist.
+     */
+    pub fn get_array_map(&self, section: &str, name: &str) -> Vec<(&str, &str)> {
+        self.section(section)
+            .into_iter()
+            .flat_map(|section| section.iter())
+            .filter_map(|(key, value)| {
+                let index = key
+                    .strip_prefix(name)?
+                    .strip_prefix('[')?
+                    .strip_suffix(']')?;
+                (!index.is_empty()).then_some((index, value))
+            })
+            .collect()
+    }
+
+    /**
+     * Returns all properties of a section without consuming the parser.
+     *
+     * # Arguments
+     * * `section` - The name of the section to look up.
+     *
+     * # Returns
+     * Returns `Some` with a `Properties` map of the key-value pairs in the section, or `None`
+     * if the section does not exist.
+     */
+    pub fn get_section(&self, section: &str) -> Option<&Properties> {
+        self.lookup(section).map(|(_, properties)| properties)
+    }
+
+    /**
+     * Returns the properties of every block of a section, in the order they appear in the
+     * input. Sections only have several blocks when parsing with
+     * `DuplicateSectionPolicy::Separate`; the first block is the one used by the other getters.
+     *
+     * # Arguments
+     * * `section` - The name of the section to look up.
+     *
+     * # Returns
+     * Returns a `Vec` of the blocks, which is empty if the section does not exist.
+     *
+     * ```rust
+     * use dotini::{DuplicateSectionPolicy, INIParser, ParserOptions};
+     *
+     * let content = "[server]\nhost = a\n[server]\nhost = b\n";
+     * assert_eq!(INIParser::from_string(content).unwrap().get("server", "host"), Some("b"));
+     *
+     * let options = ParserOptions::new().duplicate_sections(DuplicateSectionPolicy::Separate);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * let hosts: Vec<_> = parser.get_section_all("server").iter().map(|block| &block["host"]).collect();
+     * assert_eq!(hosts, ["a", "b"]);
+     * assert_eq!(parser.get("server", "host"), Some("a"));
+     * ```
+     */
+    pub fn get_section_all(&self, section: &str) -> Vec<&Properties> {
+        let Some((section, first)) = self.lookup(section) else {
+            return Vec::new();
+        };
+        std::iter::once(first)
+            .chain(self.extra_sections.get(section).into_iter().flatten())
+            .collect()
+    }
+
+    /**
+     * Returns a read-only view of a section.
+     *
+     * # Arguments
+     * * `section` - The name of the section to look up.
+     *
+     * # Returns
+     * Returns `Some` with a `Section` borrowing the parser, or `None` if the section does not
+     * exist.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[db]\nhost=localhost\nport=5432\n").unwrap();
+     * let db = parser.section("db").unwrap();
+     * assert_eq!(db.name(), "db");
+     * assert_eq!(db.get("host"), Some("localhost"));
+     * assert_eq!(db.len(), 2);
+     * assert_eq!(&db["port"], "5432");
+     * assert_eq!(&parser[("db", "host")], "localhost");
+     * ```
+     */
+    pub fn section(&self, section: &str) -> Option<Section<'_>> {
+        self.lookup(section).map(|(name, properties)| {
+            Section::new(
+                name,
+                properties,
+                self.options.case_insensitive,
+                self.options.normalization,
+            )
+        })
+    }
+
+    /**
+     * Returns a mutable view of a section.
+     *
+     * # Arguments
+     * * `section` - The name of the section to look up.
+     *
+     * # Returns
+     * Returns `Some` with a `SectionMut` borrowing the parser, or `None` if the section does
+     * not exist.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[db]\nhost=localhost\nport=5432\n").unwrap();
+     * let mut db = parser.section_mut("db").unwrap();
+     * db.set("user", "admin");
+     * db.rename_key("host", "hostname");
+     * db.remove("port");
+     * assert_eq!(parser.get("db", "hostname"), Some("localhost"));
+     * assert_eq!(parser.get("db", "user"), Some("admin"));
+     * assert_eq!(parser.get("db", "port"), None);
+     * ```
+     *
+     * Edits through the view keep the collected values and spans in step, like the parser's
+     * own methods:
+     *
+     * ```rust
+     * use dotini::{DuplicateKeyPolicy, INIParser, ParserOptions};
+     *
+     * let options = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::CollectAll);
+     * let content = "[a]\nk = 1\nk = 2\nj = 3\nj = 4\n";
+     * let mut parser = INIParser::from_string_with_options(content, options).unwrap();
+     * parser.section_mut("a").unwrap().remove("k");
+     * parser.section_mut("a").unwrap().set("k", "5");
+     * assert_eq!(parser.key_span("a", "k"), None);
+     * assert_eq!(parser.get_all("a", "k"), ["5"]);
+     *
+     * parser.section_mut("a").unwrap().rename_key("j", "i");
+     * assert_eq!(parser.get_all("a", "i"), ["3", "4"]);
+     * assert_eq!(parser.key_span("a", "i").unwrap().line, 5);
+     * assert!(parser.get_all("a", "j").is_empty());
+     * ```
+     */
+    pub fn section_mut(&mut self, section: &str) -> Option<SectionMut<'_>> {
+        let index = self.section_index(section)?;
+        let (sorted, ignore_case) = (self.options.sorted(), self.options.case_insensitive);
+        let normalization = self.options.normalization;
+        let name = self.output.get_index(index)?.0.clone();
+        let side_tables = section::SideTables {
+            values: self.multi_values.get_mut(&name),
+            spans: self.spans.properties_mut(&name),
+        };
+        Some(SectionMut::new(
+            name,
+            &mut self.output[index],
+            side_tables,
+            sorted,
+            ignore_case,
+            normalization,
+        ))
+    }
+
+    /**
+     * Returns a read-only view of the properties that appear before the first section header.
+     *
+     * # Returns
+     * Returns `Some` with a `Section` view of the default section, or `None` if the config has
+     * no such properties.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("version = 2\n[server]\nport = 80\n").unwrap();
+     * assert_eq!(parser.globals().unwrap().get("version"), Some("2"));
+     * assert!(INIParser::from_string("[server]\n").unwrap().globals().is_none());
+     * ```
+     */
+    pub fn globals(&self) -> Option<Section<'_>> {
+        self.section(&self.options.default_section)
+    }
+
+    /**
+     * Returns a read-only view of a git-style subsection, such as `[remote "origin"]`.
+     *
+     * # Arguments
+     * * `section` - The name of the section, e.g. `remote`.
+     * * `subsection` - The unquoted name of the subsection, e.g. `origin`.
+     *
+     * # Returns
+     * Returns `Some` with a `Section` view, or `None` if the subsection does not exist.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[core]\nbare = false\n[remote \"origin\"]\nurl = git@example.com:app.git\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * let origin = parser.subsection("remote", "origin").unwrap();
+     * assert_eq!(origin.get("url"), Some("git@example.com:app.git"));
+     * assert_eq!(origin.name(), "remote \"origin\"");
+     * assert_eq!(parser.to_ini_string().unwrap(), content.replace("\n[", "\n\n["));
+     * ```
+     */
+    pub fn subsection(&self, section: &str, subsection: &str) -> Option<Section<'_>> {
+        self.section(&section::subsection_name(section, subsection))
+    }
+
+    /**
+     * Returns the names of the git-style subsections of a section, in the order they appear.
+     *
+     * # Arguments
+     * * `section` - The name of the section, e.g. `branch`.
+     *
+     * # Returns
+     * Returns a `Vec` of the unquoted subsection names, which is empty if there are none.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[branch \"main\"]\nremote = origin\n[branch \"fix/\\\"quotes\\\"\"]\nremote = fork\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.subsections("branch"), ["main", "fix/\"quotes\""]);
+     * assert_eq!(parser.subsection("branch", "fix/\"quotes\"").unwrap().get("remote"), Some("fork"));
+     * ```
+     */
+    pub fn subsections(&self, section: &str) -> Vec<String> {
+        let ignore_case = self.options.case_insensitive;
+        self.output
+            .keys()
+            .filter_map(|name| section::split_subsection(name))
+            .filter(|(name, _)| section::same_name(name, section, ignore_case))
+            .map(|(_, subsection)| subsection)
+            .collect()
+    }
+
+    /**
+     * Returns the sections arranged as a tree, reading dots in section names as nesting.
+     *
+     * # Returns
+     * Returns the root `SectionTree`, which holds the properties of the default section.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[database]\nname = app\n[database.replica.eu]\nhost = eu.db\n[database.replica.us]\nhost = us.db\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * let tree = parser.tree();
+     * let replica = tree.find("database.replica").unwrap();
+     * assert!(replica.properties().is_none());
+     * let hosts: Vec<_> = replica.children().map(|(region, node)| (region, node.get("host").unwrap())).collect();
+     * assert_eq!(hosts, [("eu", "eu.db"), ("us", "us.db")]);
+     * assert_eq!(tree.child("database").unwrap().get("name"), Some("app"));
+     * assert_eq!(replica.child("eu").unwrap().path(), "database.replica.eu");
+     * ```
+     */
+    pub fn tree(&self) -> SectionTree<'_> {
+        SectionTree::new(&self.output, &self.options.default_section)
+    }
+
+    /**
+     * Returns an iterator over the names of all sections in the order they appear, including
+     * sections without any properties.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[b]\nx=1\n[a]\n").unwrap();
+     * let sections: Vec<&str> = parser.sections().collect();
+     * assert_eq!(sections, ["b", "a"]);
+     * ```
+     */
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.output.keys().map(String::as_str)
+    }
+
+    /**
+     * Returns an iterator over every property of the config as `(section, key, value)` triples.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[a]\nx=1\n[b]\ny=2\n").unwrap();
+     * let triples: Vec<_> = parser.iter().collect();
+     * assert_eq!(triples, [("a", "x", "1"), ("b", "y", "2")]);
+     * ```
+     */
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(&self.output)
     }
+
+    /**
+     * Returns an iterator over the property names of a section.
+     *
+     * # Arguments
+     * * `section` - The name of the section to list.
+     *
+     * # Returns
+     * Returns an iterator over the keys of the section, which is empty if the section does
+     * not exist.
+     */
+    pub fn keys<'a>(&'a self, section: &str) -> impl Iterator<Item = &'a str> {
+        self.get_section(section)
+            .into_iter()
+            .flat_map(|properties| properties.keys())
+            .map(String::as_str)
+    }
+
+    /**
+     * Returns the properties of a section whose key starts with the given prefix.
+     *
+     * # Arguments
+     * * `section` - The name of the section to search.
+     * * `prefix` - The prefix the keys must start with.
+     *
+     * # Returns
+     * Returns an iterator over the matching `(key, value)` pairs, which is empty if the section
+     * does not exist.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[app]\nlog_level=info\nlog_file=app.log\nport=80\n").unwrap();
+     * let mut logging: Vec<_> = parser.keys_with_prefix("app", "log_").collect();
+     * logging.sort();
+     * assert_eq!(logging, [("log_file", "app.log"), ("log_level", "info")]);
+     * ```
+     */
+    pub fn keys_with_prefix<'a>(
+        &'a self,
+        section: &str,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.section(section)
+            .into_iter()
+            .flat_map(move |section| section.keys_with_prefix(prefix))
+    }
+
+    /// Returns `true` if the config contains a section with the given name.
+    pub fn contains_section(&self, section: &str) -> bool {
+        self.section_index(section).is_some()
+    }
+
+    /// Returns `true` if the given section exists and contains the given key.
+    pub fn contains_key(&self, section: &str, key: &str) -> bool {
+        self.get(section, key).is_some()
+    }
+
+    /**
+     * Gets the entry of a section for in-place manipulation, like `HashMap::entry`.
+     * A newly inserted section is added after the existing ones, even with `Order::Sorted`.
+     *
+     * # Arguments
+     * * `section` - The name of the section.
+     *
+     * # Returns
+     * Returns the `Entry` of the section in the inner `Sections` map.
+     */
+    pub fn entry(&mut self, section: &str) -> Entry<'_, String, Properties> {
+        let section = self.section_name(section);
+        self.output.entry(section)
+    }
+
+    /**
+     * Gets the entry of a property for in-place manipulation, creating its section if needed.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns the `Entry` of the property in the section's `Properties` map.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[cache]\nttl=60\n").unwrap();
+     * parser.entry_key("cache", "ttl").or_insert_with(|| "300".to_string());
+     * parser.entry_key("cache", "size").or_insert_with(|| "1024".to_string());
+     * assert_eq!(parser.get("cache", "ttl"), Some("60"));
+     * assert_eq!(parser.get("cache", "size"), Some("1024"));
+     * ```
+     */
+    pub fn entry_key(&mut self, section: &str, key: &str) -> Entry<'_, String, String> {
+        let ignore_case = self.options.case_insensitive;
+        let key = self.options.normalize(key).into_owned();
+        let properties = self.entry(section).or_default();
+        let key = section::stored_key(properties, &key, ignore_case).unwrap_or(&key);
+        properties.entry(key.to_string())
+    }
+
+    /**
+     * Sets the value of a property, creating the section if it does not exist yet.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     * * `value` - The new value of the property.
+     *
+     * # Returns
+     * Returns the previous value of the property, if there was one.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[server]\nport=80\n").unwrap();
+     * assert_eq!(parser.set("server", "port", "8080"), Some("80".to_string()));
+     * assert_eq!(parser.set("tls", "enabled", "true"), None);
+     * assert_eq!(parser.get("tls", "enabled"), Some("true"));
+     * ```
+     */
+    pub fn set(
+        &mut self,
+        section: &str,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Option<String> {
+        let (section, key) = self.stored_names(section, &key.into());
+        self.forget_values(&section, &key);
+        let sorted = self.options.sorted();
+        let properties = match self.output.get_index_of(&section) {
+            Some(index) => &mut self.output[index],
+            None if sorted => {
+                let (index, _) = self.output.insert_sorted(section, Properties::new());
+                &mut self.output[index]
+            }
+            None => self.output.entry(section).or_default(),
+        };
+        section::insert_property(properties, key, value.into(), sorted, false)
+    }
+
+    /**
+     * Layers another config over this one: its sections are added and its values replace
+     * those of the same keys, so that a base config can be overridden by more specific ones.
+     * The values it collected for repeated keys replace those of this config, and its
+     * repeated section blocks are appended after the ones of this config. The origin of the
+     * merged values is kept, see `origin`.
+     *
+     * # Arguments
+     * * `other` - The config to layer over this one.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[db]\nhost = localhost\nport = 5432\n").unwrap();
+     * parser.merge(INIParser::from_string("[db]\nport = 6432\n[cache]\n").unwrap());
+     * assert_eq!(parser.get("db", "host"), Some("localhost"));
+     * assert_eq!(parser.get("db", "port"), Some("6432"));
+     * assert!(parser.contains_section("cache"));
+     * ```
+     *
+     * Repeated keys and blocks are merged under the names this config stores them under:
+     *
+     * ```rust
+     * use dotini::{DuplicateKeyPolicy, DuplicateSectionPolicy, INIParser, ParserOptions};
+     *
+     * let options = ParserOptions::new().case_insensitive(true);
+     * let options = options.duplicate_keys(DuplicateKeyPolicy::CollectAll);
+     * let options = options.duplicate_sections(DuplicateSectionPolicy::Separate);
+     * let base = "[Server]\nHost = a\n";
+     * let mut parser = INIParser::from_string_with_options(base, options.clone()).unwrap();
+     * let layer = "[server]\nhost = b\nhost = c\n[server]\nhost = d\n";
+     * parser.merge(INIParser::from_string_with_options(layer, options).unwrap());
+     * assert_eq!(parser.get_all("server", "host"), ["b", "c"]);
+     * let blocks = parser.get_section_all("server");
+     * let hosts: Vec<_> = blocks.iter().flat_map(|block| block.values()).collect();
+     * assert_eq!(hosts, ["c", "d"]);
+     * assert_eq!(parser.to_string(), "[Server]\nHost = b\nHost = c\n\n[Server]\nhost = d\n");
+     * ```
+     */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(sections = other.output.len()))
+    )]
+    pub fn merge(&mut self, other: INIParser) {
+        let INIParser {
+            output,
+            multi_values,
+            extra_sections,
+            parents,
+            mut spans,
+            ..
+        } = other;
+        for (section, properties) in output {
+            self.entry(&section).or_default();
+            if let Some(span) = spans.take_section(&section) {
+                let name = self.section_name(&section);
+                self.spans.record_section(&name, span);
+            }
+            for (key, value) in properties {
+                let read = spans.take_property(&section, &key);
+                self.set(&section, key.clone(), value);
+                if let Some(read) = read {
+                    let (section, key) = self.stored_names(&section, &key);
+                    self.spans.record_property(&section, key, read);
+                }
+            }
+        }
+        for (section, keys) in multi_values {
+            for (key, values) in keys {
+                let (section, key) = self.stored_names(&section, &key);
+                self.multi_values
+                    .entry(section)
+                    .or_default()
+                    .insert(key, values);
+            }
+        }
+        for (section, blocks) in extra_sections {
+            let section = self.section_name(&section);
+            self.extra_sections
+                .entry(section)
+                .or_default()
+                .extend(blocks);
+        }
+        for (section, parent) in parents {
+            self.parents.insert(self.section_name(&section), parent);
+        }
+    }
+
+    /**
+     * Removes a property from a section.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property to remove.
+     *
+     * # Returns
+     * Returns the removed value, or `None` if the section or the key did not exist.
+     */
+    pub fn remove_key(&mut self, section: &str, key: &str) -> Option<String> {
+        let (section, key) = self.stored_names(section, key);
+        self.forget_values(&section, &key);
+        self.output.get_mut(&section)?.shift_remove(&key)
+    }
+
+    /**
+     * Removes a whole section together with its properties.
+     *
+     * # Arguments
+     * * `section` - The name of the section to remove.
+     *
+     * # Returns
+     * Returns the removed properties, or `None` if the section did not exist.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[legacy]\nmode=old\n[app]\nname=demo\n").unwrap();
+     * let legacy = parser.remove_section("legacy").unwrap();
+     * assert_eq!(legacy["mode"], "old");
+     * assert!(!parser.contains_section("legacy"));
+     * assert_eq!(parser.remove_key("app", "name"), Some("demo".to_string()));
+     * ```
+     */
+    pub fn remove_section(&mut self, section: &str) -> Option<Properties> {
+        let section = self.section_name(section);
+        self.forget_section(&section);
+        self.output.shift_remove(&section)
+    }
+
+    /**
+     * Keeps only the sections for which the predicate returns `true`, dropping the repeated
+     * blocks, collected values and parents of the others as well.
+     *
+     * # Arguments
+     * * `keep` - A closure receiving each section as a `Section` view.
+     *
+     * ```rust
+     * use dotini::{DuplicateSectionPolicy, INIParser, ParserOptions};
+     *
+     * let mut parser = INIParser::from_string("[app]\nname=demo\n[debug.log]\nlevel=trace\n").unwrap();
+     * parser.retain_sections(|section| !section.name().starts_with("debug."));
+     * assert_eq!(parser.sections().collect::<Vec<_>>(), ["app"]);
+     *
+     * let options = ParserOptions::new().duplicate_sections(DuplicateSectionPolicy::Separate);
+     * let content = "[a]\nx = 1\n[a]\nx = 2\n[b]\n";
+     * let mut parser = INIParser::from_string_with_options(content, options).unwrap();
+     * parser.retain_sections(|section| section.name() != "a");
+     * parser.set("a", "x", "3");
+     * assert_eq!(parser.get_section_all("a").len(), 1);
+     * assert!(parser.section_span("a").is_none());
+     * ```
+     */
+    pub fn retain_sections<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(Section<'_>) -> bool,
+    {
+        let (ignore_case, normalization) =
+            (self.options.case_insensitive, self.options.normalization);
+        let mut removed = Vec::new();
+        self.output.retain(|name, properties| {
+            let kept = keep(Section::new(name, properties, ignore_case, normalization));
+            if !kept {
+                removed.push(name.clone());
+            }
+            kept
+        });
+        for section in removed {
+            self.forget_section(&section);
+        }
+    }
+
+    /**
+     * Keeps only the properties for which the predicate returns `true`, those of the repeated
+     * blocks of a section included, dropping the values collected for the others.
+     * Sections are kept even if all of their properties are removed.
+     *
+     * # Arguments
+     * * `keep` - A closure receiving the section name, key and value of each property.
+     *
+     * ```rust
+     * use dotini::{DuplicateKeyPolicy, INIParser, ParserOptions};
+     *
+     * let options = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::CollectAll);
+     * let content = "[app]\nname = demo\npath = /bin\npath = /usr/bin\n";
+     * let mut parser = INIParser::from_string_with_options(content, options).unwrap();
+     * parser.retain_keys(|_, key, _| key != "path");
+     * assert_eq!(parser.get("app", "path"), None);
+     * assert_eq!(parser.get("app", "name"), Some("demo"));
+     *
+     * // Adding the key back does not bring back the values or spans of the removed one.
+     * parser.inner_mut()["app"].insert("path".to_string(), "/usr/bin".to_string());
+     * assert_eq!(parser.get_all("app", "path"), ["/usr/bin"]);
+     * assert!(parser.key_span("app", "path").is_none());
+     * ```
+     */
+    pub fn retain_keys<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&str, &str, &str) -> bool,
+    {
+        let mut removed = Vec::new();
+        for (section, properties) in self.output.iter_mut() {
+            properties.retain(|key, value| {
+                let kept = keep(section, key, value);
+                if !kept {
+                    removed.push((section.clone(), key.clone()));
+                }
+                kept
+            });
+        }
+        for (section, blocks) in self.extra_sections.iter_mut() {
+            for properties in blocks {
+                properties.retain(|key, value| keep(section, key, value));
+            }
+        }
+        for (section, key) in removed {
+            self.forget_values(&section, &key);
+        }
+    }
+
+    /**
+     * Replaces every value of the config with the result of a closure, every value collected
+     * for a repeated key and those of the repeated blocks of a section included.
+     *
+     * # Arguments
+     * * `map` - A closure receiving the section name, key and current value of each property
+     *   and returning its new value.
+     *
+     * ```rust
+     * use dotini::{DuplicateKeyPolicy, DuplicateSectionPolicy, INIParser, ParserOptions};
+     *
+     * let mut parser = INIParser::from_string("[log]\nlevel=DEBUG\n").unwrap();
+     * parser.map_values(|_, _, value| value.to_lowercase());
+     * assert_eq!(parser.get("log", "level"), Some("debug"));
+     *
+     * let options = ParserOptions::new()
+     *     .duplicate_keys(DuplicateKeyPolicy::CollectAll)
+     *     .duplicate_sections(DuplicateSectionPolicy::Separate);
+     * let content = "[s]\nk = a\nk = b\n[s]\nk = c\n";
+     * let mut parser = INIParser::from_string_with_options(content, options).unwrap();
+     * parser.map_values(|_, _, value| value.to_uppercase());
+     * assert_eq!(parser.get_all("s", "k"), ["A", "B"]);
+     * assert_eq!(parser.get_section_all("s")[1]["k"], "C");
+     * assert_eq!(parser.to_ini_string().unwrap(), "[s]\nk = A\nk = B\n\n[s]\nk = C\n");
+     * ```
+     */
+    pub fn map_values<F>(&mut self, mut map: F)
+    where
+        F: FnMut(&str, &str, &str) -> String,
+    {
+        for (section, properties) in self.output.iter_mut() {
+            let mut collected = self.multi_values.get_mut(section);
+            for (key, value) in properties.iter_mut() {
+                // Values changed through `inner_mut` no longer match the collected ones.
+                let values = collected
+                    .as_mut()
+                    .and_then(|collected| collected.get_mut(key))
+                    .filter(|values| values.last() == Some(value));
+                match values {
+                    Some(values) => {
+                        for collected in values.iter_mut() {
+                            *collected = map(section, key, collected);
+                        }
+                        value.clone_from(&values[values.len() - 1]);
+                    }
+                    None => *value = map(section, key, value),
+                }
+            }
+        }
+        for (section, blocks) in self.extra_sections.iter_mut() {
+            for (key, value) in blocks.iter_mut().flatten() {
+                *value = map(section, key, value);
+            }
+        }
+    }
+
+    /**
+     * Renames a section while keeping its properties and position, together with its repeated
+     * blocks, collected values, parent and spans. A section already stored under the new name
+     * is overwritten, none of what it had staying behind.
+     *
+     * # Arguments
+     * * `from` - The current name of the section.
+     * * `to` - The new name of the section.
+     *
+     * # Returns
+     * Returns `true` if the section existed and was renamed.
+     *
+     * ```rust
+     * use dotini::{DuplicateKeyPolicy, INIParser, ParserOptions};
+     *
+     * let options = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::CollectAll);
+     * let content = "[base]\n[a]\nk = 1\n[c : base]\nk = 2\nk = 3\n";
+     * let mut parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert!(parser.rename_section("a", "c"));
+     * assert_eq!(parser.section_span("c").unwrap().line, 2);
+     * assert_eq!(parser.key_span("c", "k").unwrap().line, 3);
+     * assert_eq!(parser.get_all("c", "k"), ["1"]);
+     * assert_eq!(parser.to_ini_string().unwrap(), "[base]\n\n[c]\nk = 1\n");
+     * ```
+     */
+    pub fn rename_section(&mut self, from: &str, to: impl Into<String>) -> bool {
+        let from = self.section_name(from);
+        let to = self.options.normalize(&to.into()).into_owned();
+        let renamed = section::rename_entry(&mut self.output, &from, to.clone(), false);
+        if !renamed {
+            return false;
+        }
+        if from != to {
+            self.forget_section(&to);
+            if let Some(values) = self.multi_values.remove(&from) {
+                self.multi_values.insert(to.clone(), values);
+            }
+            if let Some(blocks) = self.extra_sections.remove(&from) {
+                self.extra_sections.insert(to.clone(), blocks);
+            }
+            if let Some(parent) = self.parents.remove(&from) {
+                self.parents.insert(to.clone(), parent);
+            }
+            self.spans.rename_section(&from, to);
+        }
+        if self.options.sorted() {
+            self.output.sort_keys();
+        }
+        true
+    }
+
+    /**
+     * Renames a property of a section while keeping its value.
+     * A property already stored under the new name is overwritten.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `from` - The current name of the property.
+     * * `to` - The new name of the property.
+     *
+     * # Returns
+     * Returns `true` if the property existed and was renamed.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[net]\ntimeout=30\n").unwrap();
+     * assert!(parser.rename_key("net", "timeout", "timeout_secs"));
+     * assert!(parser.rename_section("net", "network"));
+     * assert_eq!(parser.get("network", "timeout_secs"), Some("30"));
+     * ```
+     */
+    pub fn rename_key(&mut self, section: &str, from: &str, to: impl Into<String>) -> bool {
+        self.section_mut(section)
+            .is_some_and(|mut properties| properties.rename_key(from, to))
+    }
+
+    /**
+     * Copies the keys each section inherits through a `[child : parent]` header into the
+     * section itself, so they show up in section views and iteration. Keys of the section
+     * keep precedence over those of its ancestors, and nearer ancestors over farther ones.
+     * Afterwards the sections no longer have parents and are written with plain headers.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[common]\nhost = localhost\ndebug = true\n[production : common]\ndebug = false\n";
+     * let mut parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("production", "host"), Some("localhost"));
+     * assert_eq!(parser.section("production").unwrap().len(), 1);
+     *
+     * parser.resolve_inheritance();
+     * let production: Vec<_> = parser.section("production").unwrap().iter().collect();
+     * assert_eq!(production, [("debug", "false"), ("host", "localhost")]);
+     * ```
+     */
+    pub fn resolve_inheritance(&mut self) {
+        let mut inherited = Vec::new();
+        for child in self.parents.keys() {
+            let mut properties = Properties::new();
+            for ancestor in self.ancestors(child) {
+                for (key, value) in ancestor.iter() {
+                    properties
+                        .entry(key.to_string())
+                        .or_insert_with(|| value.to_string());
+                }
+            }
+            inherited.push((child.clone(), properties));
+        }
+        let sorted = self.options.sorted();
+        for (child, properties) in inherited {
+            let Some(own) = self.output.get_mut(&child) else {
+                continue;
+            };
+            for (key, value) in properties {
+                if !own.contains_key(&key) {
+                    section::insert_property(own, key, value, sorted, false);
+                }
+            }
+        }
+        self.parents.clear();
+    }
+
+    /**
+     * Sorts the sections by name, leaving the order of keys within each section untouched.
+     *
+     * # Arguments
+     * * `ignore_case` - Compares names without regard to case; names that only differ in case
+     *   keep a stable, case-sensitive order.
+     */
+    pub fn sort_sections(&mut self, ignore_case: bool) {
+        self.output
+            .sort_by(|a, _, b, _| compare_names(a, b, ignore_case));
+    }
+
+    /**
+     * Sorts the keys of every section by name, leaving the order of sections untouched.
+     *
+     * Together with `sort_sections`, this gives a canonical form that serializes the same way
+     * regardless of the order of the input, which keeps diffs and snapshot tests stable.
+     * A parser using `Order::Sorted` already keeps keys sorted case-sensitively; sorting it
+     * case-insensitively does not change where later insertions go.
+     *
+     * # Arguments
+     * * `ignore_case` - Compares names without regard to case; names that only differ in case
+     *   keep a stable, case-sensitive order.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_string("[b]\nZeta=1\nalpha=2\n[A]\n").unwrap();
+     * parser.sort_sections(true);
+     * parser.sort_keys(true);
+     * assert_eq!(parser.sections().collect::<Vec<_>>(), ["A", "b"]);
+     * assert_eq!(parser.keys("b").collect::<Vec<_>>(), ["alpha", "Zeta"]);
+     * ```
+     */
+    pub fn sort_keys(&mut self, ignore_case: bool) {
+        for properties in self.output.values_mut() {
+            properties.sort_by(|a, _, b, _| compare_names(a, b, ignore_case));
+        }
+    }
+
+    /**
+     * Returns the value of a property converted to `T` using its `FromStr` implementation.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the converted value, `InIParseError::MissingKey`
+     * if the property does not exist, or `InIParseError::ConversionError` if the value cannot
+     * be parsed as `T`.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[server]\nport=8080\n").unwrap();
+     * let port: u16 = parser.get_as("server", "port").unwrap();
+     * assert_eq!(port, 8080);
+     * assert!(parser.get_as::<u16>("server", "host").is_err());
+     * ```
+     */
+    pub fn get_as<T>(&self, section: &str, key: &str) -> INIParserResult<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        convert(section, key, self.require(section, key)?)
+    }
+
+    /**
+     * Returns the value of a property converted to `T`, or `None` if it does not exist.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the converted value if the property exists, or
+     * `InIParseError::ConversionError` if the value cannot be parsed as `T`.
+     */
+    pub fn get_opt<T>(&self, section: &str, key: &str) -> INIParserResult<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(section, key)
+            .map(|value| convert(section, key, value))
+            .transpose()
+    }
+
+    /**
+     * Returns the value of a property converted to `T`, falling back to `default` if it does
+     * not exist.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     * * `default` - The value to return when the property is missing.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the converted value or the default, or
+     * `InIParseError::ConversionError` if the value exists but cannot be parsed as `T`.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[server]\nport=8080\n").unwrap();
+     * assert_eq!(parser.get_or("server", "port", 80u16).unwrap(), 8080);
+     * assert_eq!(parser.get_or("server", "workers", 4u8).unwrap(), 4);
+     * ```
+     */
+    pub fn get_or<T>(&self, section: &str, key: &str, default: T) -> INIParserResult<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Ok(self.get_opt(section, key)?.unwrap_or(default))
+    }
+
+    /**
+     * Returns the value of a property interpreted as a boolean.
+     *
+     * `true`, `yes`, `on` and `1` are read as `true`, while `false`, `no`, `off` and `0` are
+     * read as `false`. The comparison is case-insensitive.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the boolean, `InIParseError::MissingKey` if the
+     * property does not exist, or `InIParseError::InvalidBool` for any other value.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[log]\nenabled=Yes\ncolor=off\nlevel=debug\n").unwrap();
+     * assert!(parser.get_bool("log", "enabled").unwrap());
+     * assert!(!parser.get_bool("log", "color").unwrap());
+     * assert!(parser.get_bool("log", "level").is_err());
+     * ```
+     */
+    pub fn get_bool(&self, section: &str, key: &str) -> INIParserResult<bool> {
+        let value = self.require(section, key)?;
+        parse_bool(value).ok_or_else(|| {
+            InIParseError::InvalidBool(ValueError::new(section, key, value, "boolean", None))
+        })
+    }
+
+    /**
+     * Returns the value of a property interpreted as a signed integer.
+     *
+     * Besides plain decimal numbers, the value may use a `0x`, `0o` or `0b` prefix for
+     * hexadecimal, octal and binary notation, and `_` as a digit separator (`1_000_000`).
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the integer, `InIParseError::MissingKey` if the
+     * property does not exist, or `InIParseError::InvalidNumber` if it is not a valid integer.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[mcu]\nmask=0x1F\nmode=0o755\nbaud=115_200\n").unwrap();
+     * assert_eq!(parser.get_int("mcu", "mask").unwrap(), 31);
+     * assert_eq!(parser.get_int("mcu", "mode").unwrap(), 493);
+     * assert_eq!(parser.get_int("mcu", "baud").unwrap(), 115_200);
+     *
+     * let parser = INIParser::from_string("[n]\na=_1\nb=-_1\nc=1__0\nd=1_\ne=0xdead_beef\n").unwrap();
+     * for key in ["a", "b", "c", "d"] {
+     *     assert!(parser.get_int("n", key).is_err(), "{key}");
+     * }
+     * assert_eq!(parser.get_int("n", "e").unwrap(), 0xdead_beef);
+     * ```
+     */
+    pub fn get_int(&self, section: &str, key: &str) -> INIParserResult<i64> {
+        let value = self.require(section, key)?;
+        parse_int(value).map_err(|reason| {
+            InIParseError::InvalidNumber(ValueError::new(
+                section,
+                key,
+                value,
+                "integer",
+                Some(reason),
+            ))
+        })
+    }
+
+    /**
+     * Returns the value of a property interpreted as a floating point number.
+     *
+     * `_` may be used as a digit separator, as with `get_int`.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the number, `InIParseError::MissingKey` if the
+     * property does not exist, or `InIParseError::InvalidNumber` if it is not a valid number.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[n]\nrate=1_000.5\na=1_.5\nb=-_1\nc=1__0\nd=1e_5\n").unwrap();
+     * assert_eq!(parser.get_float("n", "rate").unwrap(), 1000.5);
+     * for key in ["a", "b", "c", "d"] {
+     *     assert!(parser.get_float("n", key).is_err(), "{key}");
+     * }
+     * ```
+     */
+    pub fn get_float(&self, section: &str, key: &str) -> INIParserResult<f64> {
+        let value = self.require(section, key)?;
+        parse_float(value).map_err(|reason| {
+            InIParseError::InvalidNumber(ValueError::new(
+                section,
+                key,
+                value,
+                "number",
+                Some(reason),
+            ))
+        })
+    }
+
+    /// Returns the ancestors of a section declared with `[child : parent]` headers, nearest
+    /// first. Missing parents end the chain and cycles are cut at the first repeat.
+    fn ancestors(&self, section: &str) -> Vec<Section<'_>> {
+        let mut ancestors: Vec<Section<'_>> = Vec::new();
+        let mut current = section;
+        while let Some(parent) = self.parents.get(current) {
+            let Some(view) = self.section(parent) else {
+                break;
+            };
+            if view.name() == section || ancestors.iter().any(|seen| seen.name() == view.name()) {
+                break;
+            }
+            current = view.name();
+            ancestors.push(view);
+        }
+        ancestors
+    }
+
+    /// Looks up a key in the ancestors of a section, nearest first.
+    fn inherited(&self, section: &str, key: &str) -> Option<&str> {
+        self.ancestors(section)
+            .into_iter()
+            .find_map(|ancestor| ancestor.get(key))
+    }
+
+    /// Looks up a key in the `ParserOptions::fallback_section`, if there is one.
+    fn fallback(&self, key: &str) -> Option<&str> {
+        let fallback = self.options.fallback_section.as_deref()?;
+        self.section(fallback)?.get(key)
+    }
+
+    /// Returns the position of a section, ignoring case with `ParserOptions::case_insensitive`.
+    /// The name is normalized first with `ParserOptions::normalize_names`.
+    fn section_index(&self, section: &str) -> Option<usize> {
+        let section = self.options.normalize(section);
+        section::find_key(&self.output, &section, self.options.case_insensitive)
+    }
+
+    /// Looks up a section together with the name it is stored under.
+    fn lookup(&self, section: &str) -> Option<(&String, &Properties)> {
+        self.output.get_index(self.section_index(section)?)
+    }
+
+    /// Returns the name a section is stored under, or the given name if it does not exist.
+    fn section_name(&self, section: &str) -> String {
+        match self.lookup(section) {
+            Some((name, _)) => name.clone(),
+            None => self.options.normalize(section).into_owned(),
+        }
+    }
+
+    /// Returns the section and key names a property is stored under, falling back to the
+    /// given names for the parts that do not exist yet.
+    fn stored_names(&self, section: &str, key: &str) -> (String, String) {
+        let key = self.options.normalize(key);
+        let Some((name, properties)) = self.lookup(section) else {
+            return (
+                self.options.normalize(section).into_owned(),
+                key.into_owned(),
+            );
+        };
+        let key = section::stored_key(properties, &key, self.options.case_insensitive)
+            .map_or(key.as_ref(), String::as_str);
+        (name.clone(), key.to_string())
+    }
+
+    /// Drops the repeated blocks, collected values, parent and spans of a section.
+    fn forget_section(&mut self, section: &str) {
+        self.multi_values.remove(section);
+        self.extra_sections.remove(section);
+        self.parents.remove(section);
+        self.spans.forget_section(section);
+    }
+
+    /// Drops the values collected for a repeated key.
+    fn forget_values(&mut self, section: &str, key: &str) {
+        if let Some(values) = self.multi_values.get_mut(section) {
+            values.remove(key);
+        }
+        self.spans.forget_property(section, key);
+    }
+
+    /// Looks up a property, turning its absence into `InIParseError::MissingKey`.
+    fn require(&self, section: &str, key: &str) -> INIParserResult<&str> {
+        self.get(section, key)
+            .ok_or_else(|| InIParseError::MissingKey(format!("{section}.{key}")))
+    }
+
+    /**
+     * Parses an INI-formatted string and returns an `INIParser` struct containing the parsed content.
+     *
+     * # Arguments
+     * * `content` - An INI-formatted string to parse.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the parsed `INIParser` struct, or an `INIParseError`
+     * if there is an issue parsing the content.
+     */
+    fn parse(content: &str, options: ParserOptions) -> INIParserResult<Self> {
+        Self::parse_from(content, None, options)
+    }
+
+    /// Parses the content of the file at `path`, or of a string when `path` is `None`, in which
+    /// case included files are looked up relative to the working directory.
+    fn parse_from(
+        content: &str,
+        path: Option<&Path>,
+        options: ParserOptions,
+    ) -> INIParserResult<Self> {
+        let mut parser = Self::from_map(Sections::new());
+        parser.options = options;
+        parser.read_all(content, path)?;
+        Ok(parser)
+    }
+
+    /// Reads the content of the file at `path`, or of a string, into an empty parser.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = ?path, bytes = content.len()))
+    )]
+    fn read_all(&mut self, content: &str, path: Option<&Path>) -> INIParserResult<()> {
+        let mut including: Vec<PathBuf> = path
+            .and_then(|path| fs::canonicalize(path).ok())
+            .into_iter()
+            .collect();
+        let read = self.read(content, path, &mut including, 0);
+        self.folded = section::FoldedNames::default();
+        let line_ending = read?;
+        self.line_ending = line_ending.unwrap_or_default();
+        if self.options.sorted() {
+            self.output.sort_keys();
+            self.output.values_mut().for_each(Properties::sort_keys);
+            self.extra_sections
+                .values_mut()
+                .flatten()
+                .for_each(Properties::sort_keys);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            sections = self.output.len(),
+            warnings = self.warnings.len(),
+            "parsed"
+        );
+        Ok(())
+    }
+
+    /**
+     * Reads INI text into the parser, following its `!include` directives.
+     *
+     * # Arguments
+     * * `content` - The INI text to read.
+     * * `path` - The file the text comes from, which included paths are relative to.
+     * * `including` - The files being read, outermost first, used to detect include cycles.
+     * * `depth` - The number of includes already being followed.
+     *
+     * # Returns
+     * Returns the line ending used by the text, or `None` if it has no line breaks.
+     */
+    fn read(
+        &mut self,
+        content: &str,
+        path: Option<&Path>,
+        including: &mut Vec<PathBuf>,
+        depth: usize,
+    ) -> INIParserResult<Option<LineEnding>> {
+        let options = self.options.clone();
+        lexer::check_size(content, &options)?;
+        let mut state = ReadState {
+            options: &options,
+            section: self
+                .folded
+                .section(options.default_section.clone(), options.case_insensitive),
+            skipping: false,
+            block: None,
+            headed: false,
+            headers: Vec::new(),
+            line: 1,
+            counted: 0,
+        };
+        let mut line_ending = None;
+
+        for line in lexer::lines(content, &options) {
+            if line_ending.is_none() {
+                line_ending = LineEnding::from_eol(line.eol);
+            }
+            state.line += content[state.counted..line.start].matches('\n').count();
+            state.counted = line.start;
+            match self.read_line(content, path, &line, &mut state, including, depth) {
+                Err(err) if state.options.lenient && !err.is_limit() => self.errors.push(err),
+                read => read?,
+            }
+        }
+        for (name, warning) in state.headers {
+            if self.output.get(&name).is_some_and(Properties::is_empty) {
+                self.warnings.push(warning);
+            }
+        }
+        Ok(line_ending)
+    }
+
+    /// Records a warning about the byte `offset` of `content`.
+    fn warn(&mut self, content: &str, offset: usize, kind: WarningKind, message: String) {
+        self.warnings
+            .push(Warning::new(content, offset, kind, message));
+    }
+
+    /// Reads a single line of `content` into the parser, tracking the section it belongs to
+    /// in `state`.
+    fn read_line(
+        &mut self,
+        content: &str,
+        path: Option<&Path>,
+        line: &lexer::RawLine,
+        state: &mut ReadState<'_>,
+        including: &mut Vec<PathBuf>,
+        depth: usize,
+    ) -> INIParserResult<()> {
+        let options = state.options;
+        let token = lexer::tokenize(content, line, options)?;
+        if let (Token::Section { .. } | Token::Property { .. }, Some((at, c))) =
+            (&token, lexer::suspicious_whitespace(line.text))
+        {
+            let message = format!("suspicious whitespace character {c:?}");
+            self.warn(
+                content,
+                line.start + at,
+                WarningKind::SuspiciousWhitespace,
+                message,
+            );
+        }
+        match token {
+            Token::Blank | Token::Comment => (),
+            Token::Section {
+                name_span,
+                name,
+                parent,
+                selected,
+            } => {
+                state.headed = true;
+                state.skipping = !selected;
+                if state.skipping {
+                    return Ok(());
+                }
+                state.section = self.folded.section(name, options.case_insensitive);
+                state.block = None;
+                if let Some(parent) = parent {
+                    self.parents.insert(state.section.clone(), parent);
+                }
+                if state.headers.iter().all(|(name, _)| *name != state.section) {
+                    let offset = line.start + name_span.start;
+                    let message = format!("section `{}` has no properties", state.section);
+                    let warning = Warning::new(content, offset, WarningKind::EmptySection, message);
+                    state.headers.push((state.section.clone(), warning));
+                }
+                if !self.output.contains_key(&state.section) {
+                    self.admit_section()?;
+                    self.output
+                        .insert(state.section.to_string(), Properties::new());
+                    let span = state.span(path, line.start, name_span);
+                    self.spans.record_section(&state.section, span);
+                    return Ok(());
+                }
+                let policy = match options.strict {
+                    true => DuplicateSectionPolicy::Error,
+                    false => options.duplicate_sections,
+                };
+                match policy {
+                    DuplicateSectionPolicy::Merge => (),
+                    DuplicateSectionPolicy::Error => {
+                        return Err(lexer::kind_error_at(
+                            content,
+                            line.start + name_span.start,
+                            ParseErrorKind::DuplicateSection,
+                            &format!("duplicate section `{}`", state.section),
+                        ));
+                    }
+                    DuplicateSectionPolicy::Separate => {
+                        self.admit_section()?;
+                        let blocks = self
+                            .extra_sections
+                            .entry(state.section.to_string())
+                            .or_default();
+                        blocks.push(Properties::new());
+                        state.block = Some(blocks.len() - 1);
+                    }
+                }
+            }
+            Token::Property { .. } | Token::Include { .. } if state.skipping => (),
+            Token::Include {
+                path_span,
+                path: target,
+                dir,
+            } => {
+                let target = match path.and_then(Path::parent) {
+                    Some(dir) => dir.join(target),
+                    None => PathBuf::from(target),
+                };
+                let offset = line.start + path_span.start;
+                if dir {
+                    let files = option_files(&target).map_err(|err| {
+                        let message = format!("cannot include {}: {err}", target.display());
+                        lexer::kind_error_at(content, offset, ParseErrorKind::Include, &message)
+                    })?;
+                    for file in files {
+                        self.include(content, offset, &file, including, depth)?;
+                    }
+                } else {
+                    self.include(content, offset, &target, including, depth)?;
+                }
+            }
+            Token::Property {
+                key_span,
+                key,
+                value_span,
+                value,
+            } => {
+                let orphans = match options.strict {
+                    true => OrphanPolicy::Error,
+                    false => options.orphan_properties,
+                };
+                match orphans {
+                    _ if state.headed => (),
+                    OrphanPolicy::DefaultSection => (),
+                    OrphanPolicy::Ignore => return Ok(()),
+                    OrphanPolicy::Error => {
+                        return Err(lexer::kind_error_at(
+                            content,
+                            line.start + key_span.start,
+                            ParseErrorKind::OrphanProperty,
+                            &format!("property `{key}` appears before the first section header"),
+                        ));
+                    }
+                }
+                if options.strict && value_span.is_empty() {
+                    let message = format!(
+                        "key `{key}` in section `{}` has an empty value",
+                        state.section
+                    );
+                    let offset = line.start + key_span.start;
+                    self.warn(content, offset, WarningKind::EmptyValue, message);
+                }
+                let (section, key, block) = match options.migration(&state.section, &key) {
+                    Some(migration) => {
+                        let offset = line.start + key_span.start;
+                        let mut message =
+                            format!("key `{key}` in section `{}` is deprecated", state.section);
+                        let (section, key) = match &migration.to {
+                            Some((target, new_key)) => {
+                                message += &format!(", use `{new_key}` in section `{target}`");
+                                let target = options.normalize(target).into_owned();
+                                let target = self.folded.section(target, options.case_insensitive);
+                                (target, options.normalize(new_key).into_owned())
+                            }
+                            None => (state.section.clone(), key),
+                        };
+                        self.warn(content, offset, WarningKind::Deprecated, message);
+                        let block = state.block.filter(|_| section == state.section);
+                        (section, key, block)
+                    }
+                    None => (state.section.clone(), key, state.block),
+                };
+                if block.is_none() && !self.output.contains_key(&section) {
+                    self.admit_section()?;
+                }
+                let properties = match block {
+                    Some(index) => &mut self
+                        .extra_sections
+                        .get_mut(&section)
+                        .ok_or(InIParseError::Unreachable)?[index],
+                    None => self.output.entry(section.to_string()).or_default(),
+                };
+                let key = self
+                    .folded
+                    .key(&section, block, key, options.case_insensitive);
+                if let Some(limit) = options.max_keys_per_section {
+                    if properties.len() >= limit && !properties.contains_key(&key) {
+                        return Err(InIParseError::TooManyKeys {
+                            section: section.clone(),
+                            limit,
+                        });
+                    }
+                }
+                let spans = (
+                    state.span(path, line.start, key_span.clone()),
+                    state.span(path, line.start, value_span),
+                );
+                if options.empty_resets && value.is_empty() && block.is_none() {
+                    properties.insert(key.clone(), value);
+                    self.spans.record_property(&section, key.clone(), spans);
+                    self.multi_values
+                        .entry(section.to_string())
+                        .or_default()
+                        .insert(key, Vec::new());
+                    return Ok(());
+                }
+                // Keys like `extensions[]` append to a php-style list whatever the policy.
+                let appended = key.ends_with("[]");
+                let policy = match options.strict {
+                    true => DuplicateKeyPolicy::Error,
+                    false => options.duplicate_keys,
+                };
+                let stored_key = key.clone();
+                let stored = match (properties.entry(key), policy) {
+                    (Entry::Vacant(entry), _) => {
+                        entry.insert(value);
+                        true
+                    }
+                    (Entry::Occupied(mut entry), policy)
+                        if block.is_none()
+                            && (appended || policy == DuplicateKeyPolicy::CollectAll) =>
+                    {
+                        let previous = entry.insert(value.clone());
+                        self.multi_values
+                            .entry(section.to_string())
+                            .or_default()
+                            .entry(entry.key().clone())
+                            .or_insert_with(|| vec![previous])
+                            .push(value);
+                        true
+                    }
+                    (Entry::Occupied(entry), DuplicateKeyPolicy::KeepFirst) => {
+                        let message = format!(
+                            "key `{}` in section `{}` is repeated, keeping its first value",
+                            entry.key(),
+                            section
+                        );
+                        let offset = line.start + key_span.start;
+                        self.warn(content, offset, WarningKind::DuplicateKey, message);
+                        false
+                    }
+                    (
+                        Entry::Occupied(mut entry),
+                        DuplicateKeyPolicy::KeepLast | DuplicateKeyPolicy::CollectAll,
+                    ) => {
+                        entry.insert(value);
+                        let message = format!(
+                            "key `{}` in section `{}` is repeated, replacing its earlier value",
+                            entry.key(),
+                            section
+                        );
+                        let offset = line.start + key_span.start;
+                        self.warn(content, offset, WarningKind::DuplicateKey, message);
+                        true
+                    }
+                    (Entry::Occupied(entry), DuplicateKeyPolicy::Error) => {
+                        return Err(lexer::kind_error_at(
+                            content,
+                            line.start + key_span.start,
+                            ParseErrorKind::DuplicateKey,
+                            &format!("duplicate key `{}` in section `{}`", entry.key(), section),
+                        ));
+                    }
+                };
+                if stored && block.is_none() {
+                    self.spans.record_property(&section, stored_key, spans);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Returns the errors of the lines skipped while parsing with `ParserOptions::lenient`, in
+     * the order they appear, those of included files included.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[server]\nhost = localhost\nport 8080\n[broken\n[client]\nretries = 3\n";
+     * assert!(INIParser::from_string(content).is_err());
+     *
+     * let options = ParserOptions::new().lenient(true);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("server", "host"), Some("localhost"));
+     * assert_eq!(parser.get("client", "retries"), Some("3"));
+     * assert_eq!(parser.errors().len(), 2);
+     * ```
+     */
+    pub fn errors(&self) -> &[InIParseError] {
+        &self.errors
+    }
+
+    /// Returns the warnings about suspicious but valid text found while parsing, in the order
+    /// they were found, those of included files included. See `Warning` for an example.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Rejects a new section, or a new block of a repeated one, once the config holds
+    /// `ParserOptions::max_sections` of them.
+    fn admit_section(&self) -> INIParserResult<()> {
+        let Some(limit) = self.options.max_sections else {
+            return Ok(());
+        };
+        let blocks: usize = self.extra_sections.values().map(Vec::len).sum();
+        if self.output.len() + blocks >= limit {
+            return Err(InIParseError::TooManySections { limit });
+        }
+        Ok(())
+    }
+
+    /// Reads an included file into the parser, rejecting include cycles and includes nested
+    /// deeper than `ParserOptions::max_include_depth`. Errors point at the directive.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(file = %target.display(), depth))
+    )]
+    fn include(
+        &mut self,
+        content: &str,
+        offset: usize,
+        target: &Path,
+        including: &mut Vec<PathBuf>,
+        depth: usize,
+    ) -> INIParserResult<()> {
+        let error = |message: String| {
+            lexer::kind_error_at(content, offset, ParseErrorKind::Include, &message)
+        };
+        if depth >= self.options.max_include_depth {
+            return Err(error(format!(
+                "includes are nested too deeply, the limit is {}",
+                self.options.max_include_depth
+            )));
+        }
+        let file = fs::canonicalize(target)
+            .map_err(|err| error(format!("cannot include {}: {err}", target.display())))?;
+        if let Some(start) = including.iter().position(|open| *open == file) {
+            let cycle: Vec<_> = including[start..]
+                .iter()
+                .chain([&file])
+                .map(|file| file.display().to_string())
+                .collect();
+            return Err(error(format!("include cycle {}", cycle.join(" -> "))));
+        }
+        let included = fs::read(&file)
+            .map_err(|err| InIParseError::FileReadError(err.to_string()))
+            .and_then(decode::decode)
+            .map_err(|err| match err {
+                InIParseError::FileReadError(err) => {
+                    error(format!("cannot include {}: {err}", target.display()))
+                }
+                err => err,
+            })?;
+        let within = |err| match err {
+            InIParseError::UnsuccessfulParse(mut err) => {
+                err.file.get_or_insert_with(|| target.to_path_buf());
+                InIParseError::UnsuccessfulParse(err)
+            }
+            err => err,
+        };
+        let (recorded, warned) = (self.errors.len(), self.warnings.len());
+        including.push(file);
+        let read = self.read(&included, Some(target), including, depth + 1);
+        let skipped: Vec<_> = self.errors.drain(recorded..).map(within).collect();
+        self.errors.extend(skipped);
+        for warning in &mut self.warnings[warned..] {
+            warning.file.get_or_insert_with(|| target.to_path_buf());
+        }
+        read.map_err(within)?;
+        including.pop();
+        Ok(())
+    }
+}
+
+/// Two configs are equal when they hold the same sections, keys and values, regardless of
+/// their order and of the options they were parsed with.
+impl PartialEq for INIParser {
+    fn eq(&self, other: &Self) -> bool {
+        self.output == other.output
+    }
+}
+
+impl Eq for INIParser {}
+
+/// Parses a config from INI text, the same as `INIParser::from_string`.
+///
+/// ```rust
+/// use dotini::INIParser;
+///
+/// let parser: INIParser = "[server]\nport=8080\n".parse().unwrap();
+/// assert_eq!(parser.get("server", "port"), Some("8080"));
+/// ```
+impl FromStr for INIParser {
+    type Err = InIParseError;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        Self::from_string(content)
+    }
+}
+
+/// Parses a config from INI text, the same as `INIParser::from_string`.
+///
+/// ```rust
+/// use dotini::{INIParser, InIParseError};
+///
+/// let parser = INIParser::try_from("[server]\nport=8080\n").unwrap();
+/// assert_eq!(parser.get("server", "port"), Some("8080"));
+///
+/// let err = INIParser::try_from("[server\nport=8080\n").unwrap_err();
+/// assert!(matches!(err, InIParseError::UnsuccessfulParse(_)));
+/// ```
+impl TryFrom<&str> for INIParser {
+    type Error = InIParseError;
+
+    fn try_from(content: &str) -> Result<Self, Self::Error> {
+        Self::from_string(content)
+    }
+}
+
+/// Wraps an already built map of sections, the same as `INIParser::from_map`, keeping the
+/// order of its sections and keys.
+///
+/// ```rust
+/// use dotini::{INIParser, Sections};
+///
+/// let sections: Sections = [
+///     ("b".to_string(), [("y".to_string(), "2".to_string())].into_iter().collect()),
+///     ("a".to_string(), [("x".to_string(), "1".to_string())].into_iter().collect()),
+/// ]
+/// .into_iter()
+/// .collect();
+/// let parser = INIParser::from(sections.clone());
+/// assert_eq!(parser.to_ini_string().unwrap(), "[b]\ny = 2\n\n[a]\nx = 1\n");
+/// assert_eq!(Sections::from(parser), sections);
+/// ```
+impl From<Sections> for INIParser {
+    fn from(output: Sections) -> Self {
+        Self::from_map(output)
+    }
+}
+
+impl From<INIParser> for Sections {
+    fn from(parser: INIParser) -> Self {
+        parser.into_inner()
+    }
+}
+
+/// Builds a config from nested `HashMap`s. Their iteration order is unspecified, so neither
+/// is the order of the sections and keys, which shows when the config is written; convert
+/// from `Sections` instead to control it.
+///
+/// ```rust
+/// use dotini::INIParser;
+/// use std::collections::HashMap;
+///
+/// let server = HashMap::from([
+///     ("host".to_string(), "localhost".to_string()),
+///     ("port".to_string(), "8080".to_string()),
+/// ]);
+/// let parser = INIParser::from(HashMap::from([("server".to_string(), server)]));
+/// assert_eq!(parser.get("server", "host"), Some("localhost"));
+/// assert_eq!(parser.get_as::<u16>("server", "port").unwrap(), 8080);
+/// assert_eq!(parser, "[server]\nport = 8080\nhost = localhost\n".parse().unwrap());
+/// ```
+impl From<HashMap<String, HashMap<String, String>>> for INIParser {
+    fn from(output: HashMap<String, HashMap<String, String>>) -> Self {
+        Self::from_map(
+            output
+                .into_iter()
+                .map(|(section, properties)| (section, properties.into_iter().collect()))
+                .collect(),
+        )
+    }
+}
+
+/// Adds `(section, key, value)` triples to the config, overwriting existing values.
+///
+/// ```rust
+/// use dotini::INIParser;
+///
+/// let mut parser = INIParser::from_string("[db]\nhost = localhost\n").unwrap();
+/// parser.extend([
+///     ("db", "host".to_string(), "db.internal".to_string()),
+///     ("cache", "ttl".to_string(), "60".to_string()),
+/// ]);
+/// assert_eq!(parser.get("db", "host"), Some("db.internal"));
+/// assert_eq!(parser.get("cache", "ttl"), Some("60"));
+/// assert_eq!(parser.sections().collect::<Vec<_>>(), ["db", "cache"]);
+/// ```
+impl<S, K, V> Extend<(S, K, V)> for INIParser
+where
+    S: AsRef<str>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn extend<I: IntoIterator<Item = (S, K, V)>>(&mut self, iter: I) {
+        for (section, key, value) in iter {
+            self.set(section.as_ref(), key, value);
+        }
+    }
+}
+
+/// Builds a config from `(section, key, value)` triples.
+///
+/// ```rust
+/// use dotini::INIParser;
+///
+/// let parser: INIParser = [("db", "host", "localhost"), ("db", "port", "5432")]
+///     .into_iter()
+///     .collect();
+/// assert_eq!(parser.get("db", "port"), Some("5432"));
+/// ```
+impl<S, K, V> FromIterator<(S, K, V)> for INIParser
+where
+    S: AsRef<str>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (S, K, V)>>(iter: I) -> Self {
+        let mut parser = Self::from_map(Sections::new());
+        parser.extend(iter);
+        parser
+    }
+}
+
+impl<'a> IntoIterator for &'a INIParser {
+    type Item = (&'a str, &'a str, &'a str);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for INIParser {
+    type Item = (String, String, String);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.output)
+    }
+}
+
+/// Indexes a section of the config, e.g. `parser["user"]["name"]`.
+///
+/// The section is returned as its stored `Properties`, since `Index` can only hand out data the
+/// parser holds and a `Section` view is built on demand. Indexing those properties by key
+/// therefore matches the stored key exactly and panics with the message of `IndexMap`; to
+/// look keys up with `ParserOptions::case_insensitive` and name normalization, and to panic
+/// with the section and key in the message, index a `Section` or by `(section, key)` instead:
+///
+/// ```rust
+/// use dotini::INIParser;
+/// use std::panic::{self, AssertUnwindSafe};
+///
+/// let parser = INIParser::from_string("[user]\nname = ada\n").unwrap();
+/// assert_eq!(parser["user"]["name"], "ada");
+/// assert_eq!(&parser.section("user").unwrap()["name"], "ada");
+/// let missing = panic::catch_unwind(AssertUnwindSafe(|| parser[("user", "missing")].len()));
+/// let message = missing.unwrap_err().downcast::<String>().unwrap();
+/// assert_eq!(*message, "key \"missing\" not found in section \"user\"");
+/// ```
+///
+/// # Panics
+/// Panics if the section does not exist.
+impl Index<&str> for INIParser {
+    type Output = Properties;
+
+    fn index(&self, section: &str) -> &Self::Output {
+        self.get_section(section)
+            .unwrap_or_else(|| panic!("section {section:?} not found"))
+    }
+}
+
+/// Mutably indexes a section of the config, e.g. `parser["user"].insert(..)`.
+///
+/// # Panics
+/// Panics if the section does not exist.
+impl IndexMut<&str> for INIParser {
+    fn index_mut(&mut self, section: &str) -> &mut Self::Output {
+        let index = self
+            .section_index(section)
+            .unwrap_or_else(|| panic!("section {section:?} not found"));
+        &mut self.output[index]
+    }
+}
+
+/// Indexes a single property by `(section, key)`, e.g. `parser[("user", "name")]`.
+///
+/// # Panics
+/// Panics if the section or the key does not exist.
+impl Index<(&str, &str)> for INIParser {
+    type Output = str;
+
+    fn index(&self, (section, key): (&str, &str)) -> &Self::Output {
+        self.get(section, key)
+            .unwrap_or_else(|| panic!("key {key:?} not found in section {section:?}"))
+    }
+}
+
+/// Lists the option files of a directory named by `!includedir`, sorted by file name. Like
+/// MySQL, only `.cnf` files are read, and `.ini` files as well on Windows.
+fn option_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        if path.is_file() && (extension == Some("cnf") || cfg!(windows) && extension == Some("ini"))
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Orders two section or key names, optionally ignoring case.
+fn compare_names(a: &str, b: &str, ignore_case: bool) -> Ordering {
+    if ignore_case {
+        section::fold_case(a)
+            .cmp(section::fold_case(b))
+            .then_with(|| a.cmp(b))
+    } else {
+        a.cmp(b)
+    }
+}
+
+/// Converts a property value with `FromStr`, reporting failures as `ConversionError`.
+fn convert<T>(section: &str, key: &str, value: &str) -> INIParserResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value.parse().map_err(|err: T::Err| {
+        let expected = type_label(std::any::type_name::<T>());
+        let reason = Some(err.to_string());
+        InIParseError::ConversionError(ValueError::new(section, key, value, expected, reason))
+    })
+}
+
+/// Shortens a type name like `core::net::ip_addr::IpAddr` to `IpAddr`, keeping generic types
+/// whole.
+fn type_label(name: &str) -> &str {
+    if name.contains('<') {
+        return name;
+    }
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Reads `true`, `yes`, `on` and `1` as `true` and their opposites as `false`, ignoring case.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Removes `_` digit separators, rejecting separators that do not sit between two digits of
+/// the given radix.
+fn strip_separators(digits: &str, radix: u32) -> Result<String, String> {
+    let is_digit = |byte: Option<&u8>| byte.is_some_and(|&byte| char::from(byte).is_digit(radix));
+    let bytes = digits.as_bytes();
+    for (index, _) in digits.match_indices('_') {
+        let before = index.checked_sub(1).and_then(|index| bytes.get(index));
+        if !is_digit(before) || !is_digit(bytes.get(index + 1)) {
+            return Err("misplaced digit separator".to_string());
+        }
+    }
+    Ok(digits.replace('_', ""))
+}
+
+/// Parses an integer written in decimal, or in hex, octal or binary with a `0x`, `0o` or `0b`
+/// prefix.
+fn parse_int(value: &str) -> Result<i64, String> {
+    let (sign, radix, digits) = int_digits(value)?;
+    i64::from_str_radix(&format!("{sign}{digits}"), radix).map_err(|err| err.to_string())
+}
+
+/// Parses an integer written like `parse_int` accepts into any primitive integer type, up to
+/// the full range of `i128` and `u128`.
+#[cfg(feature = "serde")]
+fn parse_integer<T: TryFrom<i128> + TryFrom<u128>>(value: &str) -> Result<T, String> {
+    let (sign, radix, digits) = int_digits(value)?;
+    if sign == "-" {
+        let value =
+            i128::from_str_radix(&format!("-{digits}"), radix).map_err(|err| err.to_string())?;
+        T::try_from(value).map_err(|_| "number too small to fit in target type".to_string())
+    } else {
+        let value = u128::from_str_radix(&digits, radix).map_err(|err| err.to_string())?;
+        T::try_from(value).map_err(|_| "number too large to fit in target type".to_string())
+    }
+}
+
+/// Splits an integer into its sign, radix and digits without separators.
+fn int_digits(value: &str) -> Result<(&str, u32, String), String> {
+    let (sign, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (radix, digits) = match unsigned.get(..2) {
+        Some("0x" | "0X") => (16, &unsigned[2..]),
+        Some("0o" | "0O") => (8, &unsigned[2..]),
+        Some("0b" | "0B") => (2, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+    let digits = strip_separators(digits, radix)?;
+    if digits.starts_with(['+', '-']) {
+        return Err("invalid digit found in string".to_string());
+    }
+    Ok((sign, radix, digits))
+}
+
+/// Parses a floating point number, allowing `_` digit separators.
+fn parse_float(value: &str) -> Result<f64, String> {
+    strip_separators(value, 10)?
+        .parse()
+        .map_err(|err: std::num::ParseFloatError| err.to_string())
 }
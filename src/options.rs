@@ -0,0 +1,1355 @@
+use crate::section;
+use crate::{IniDialect, DEFAULT_SECTION};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// A key registered with `ParserOptions::deprecated_key` or `ParserOptions::migrate_key`.
+#[derive(Debug, Clone)]
+pub(crate) struct KeyMigration {
+    pub(crate) section: String,
+    pub(crate) key: String,
+    /// The section and key that replace it, if any.
+    pub(crate) to: Option<(String, String)>,
+}
+
+/// The order in which the sections and keys of a config are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    /// Sections and keys keep the order in which they were parsed or added.
+    #[default]
+    Insertion,
+    /// Sections and keys are kept sorted by name, like a `BTreeMap`, so iteration and written
+    /// output are reproducible regardless of the input order.
+    Sorted,
+}
+
+/// How a key written on its own line, without a delimiter or value, is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValuelessKeys {
+    /// Such lines are errors.
+    #[default]
+    Reject,
+    /// The key is given an empty value.
+    Empty,
+    /// The key is given the value `true`, so it can be read with `INIParser::get_bool`.
+    True,
+}
+
+/// What happens when a key appears more than once in the same section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// The first value is kept and later ones are ignored.
+    KeepFirst,
+    /// Each value replaces the previous one; the key keeps its first position.
+    #[default]
+    KeepLast,
+    /// A repeated key is a parse error pointing at its second occurrence.
+    Error,
+    /// Every value is kept and can be read with `INIParser::get_all`, while the last one is
+    /// the value returned by `INIParser::get`.
+    CollectAll,
+}
+
+/// What happens when a section header appears more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateSectionPolicy {
+    /// The properties of later blocks are added to the first one, following the
+    /// `DuplicateKeyPolicy` for keys they have in common.
+    #[default]
+    Merge,
+    /// A repeated section header is a parse error.
+    Error,
+    /// Each block is kept on its own and can be read with `INIParser::get_section_all`, while the
+    /// first block is the one seen by the other getters.
+    Separate,
+}
+
+/// What happens to properties that appear before the first section header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrphanPolicy {
+    /// The properties are stored under the default section, see
+    /// `ParserOptions::default_section`.
+    #[default]
+    DefaultSection,
+    /// The properties are skipped as if they were comments.
+    Ignore,
+    /// A property before the first section header is a parse error.
+    Error,
+}
+
+/// How section and key names are normalized before they are stored or looked up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Normalization {
+    /// Names are used as written.
+    #[default]
+    None,
+    /// Names are brought into Unicode Normalization Form C, so a precomposed `é` and an `e`
+    /// followed by a combining accent are the same name.
+    Nfc,
+    /// Names are brought into Normalization Form C and lowercased, so they also match
+    /// regardless of case. Unlike `ParserOptions::case_insensitive`, which folds the same
+    /// way, the names are stored lowercased.
+    NfcCaseFold,
+}
+
+/// Which references `INIParser::get_interpolated` and `INIParser::interpolate` resolve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Values are returned as written.
+    None,
+    /// `${key}` and `${section:key}` references are replaced by the values they name.
+    #[default]
+    Extended,
+    /// `%NAME%` references are replaced by the environment variable `NAME`, as Windows
+    /// applications expand strings like `%APPDATA%\App`. References to variables that are not
+    /// set are kept, another `%` is written as `%%`, and no other references are resolved.
+    Windows,
+}
+
+/// How whitespace around keys and values is treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrimPolicy {
+    /// Whitespace around keys and values is removed.
+    #[default]
+    Both,
+    /// Whitespace around keys is removed, while a value keeps everything between the
+    /// delimiter and the end of the line or an inline comment.
+    KeysOnly,
+    /// Keys keep the whitespace up to the delimiter, including their indentation, and values
+    /// are kept as with `TrimPolicy::KeysOnly`. Quoted keys are still read without their
+    /// quotes.
+    Verbatim,
+}
+
+/// A family of INI files with its own syntax, for `ParserOptions::dialect`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// Git's `.gitconfig` and `.git/config`: `[section "subsection"]` headers, `#` and `;`
+    /// comments ending any line, escapes in quoted values, backslash continuations, repeated
+    /// keys collected into lists, valueless keys read as `true` and names looked up without
+    /// regard to case.
+    GitConfig,
+    /// systemd unit files, as described by `ParserOptions::systemd`.
+    Systemd,
+    /// PHP's `php.ini`: only `;` starts a comment, only `=` separates keys from values, and
+    /// `extension[]` style keys build arrays.
+    PhpIni,
+    /// `.editorconfig` files, as described by `ParserOptions::editorconfig`.
+    EditorConfig,
+    /// Java `.properties` files read line by line: `#` and `!` comments, `=` or `:`
+    /// delimiters, backslash continuations and values kept as written. Use
+    /// `INIParser::from_properties` for whitespace delimiters and `\uXXXX` escapes.
+    Properties,
+    /// `.env` files read line by line: `#` comments ending any line, `=` delimiters and quoted
+    /// values with escapes. Use `INIParser::from_dotenv` for `export` prefixes and `$VAR`
+    /// references.
+    Dotenv,
+    /// Windows profile files as read by `GetPrivateProfileString`: only `;` starts a comment,
+    /// only `=` separates keys from values, names are looked up without regard to case and
+    /// the first of repeated keys wins.
+    Windows,
+    /// MySQL and MariaDB option files, as described by `ParserOptions::my_cnf`.
+    MyCnf,
+    /// XDG Desktop Entry files, as described by `ParserOptions::desktop_entry`.
+    DesktopEntry,
+    /// Samba's `smb.conf`, as described by `ParserOptions::smb_conf`.
+    SmbConf,
+}
+
+/// Options controlling how INI text is parsed and how the resulting config behaves.
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    pub(crate) order: Order,
+    pub(crate) comment_prefixes: Vec<String>,
+    pub(crate) inline_comments: bool,
+    pub(crate) quoted_values: bool,
+    pub(crate) escapes: bool,
+    pub(crate) line_continuation: bool,
+    pub(crate) multiline_values: bool,
+    pub(crate) colon_delimiter: bool,
+    pub(crate) empty_values: bool,
+    pub(crate) valueless_keys: ValuelessKeys,
+    pub(crate) duplicate_keys: DuplicateKeyPolicy,
+    pub(crate) duplicate_sections: DuplicateSectionPolicy,
+    pub(crate) orphan_properties: OrphanPolicy,
+    pub(crate) case_insensitive: bool,
+    pub(crate) default_section: String,
+    pub(crate) fallback_section: Option<String>,
+    pub(crate) conditions: Vec<(String, String)>,
+    pub(crate) includes: bool,
+    pub(crate) max_include_depth: usize,
+    pub(crate) systemd_continuation: bool,
+    pub(crate) empty_resets: bool,
+    pub(crate) glob_sections: bool,
+    pub(crate) spaced_keys: bool,
+    pub(crate) custom_dialect: Option<Arc<dyn IniDialect>>,
+    pub(crate) trim: TrimPolicy,
+    pub(crate) normalization: Normalization,
+    pub(crate) key_chars: Option<fn(char) -> bool>,
+    pub(crate) interpolation: Interpolation,
+    pub(crate) env_interpolation: bool,
+    pub(crate) max_interpolation_depth: usize,
+    pub(crate) max_interpolated_length: usize,
+    pub(crate) max_input_size: Option<usize>,
+    pub(crate) max_line_length: Option<usize>,
+    pub(crate) max_sections: Option<usize>,
+    pub(crate) max_keys_per_section: Option<usize>,
+    pub(crate) lenient: bool,
+    pub(crate) strict: bool,
+    pub(crate) key_migrations: Vec<KeyMigration>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            order: Order::Insertion,
+            comment_prefixes: vec![";".to_string(), "#".to_string()],
+            inline_comments: false,
+            quoted_values: true,
+            escapes: false,
+            line_continuation: false,
+            multiline_values: false,
+            colon_delimiter: true,
+            empty_values: true,
+            valueless_keys: ValuelessKeys::Reject,
+            duplicate_keys: DuplicateKeyPolicy::KeepLast,
+            duplicate_sections: DuplicateSectionPolicy::Merge,
+            orphan_properties: OrphanPolicy::DefaultSection,
+            case_insensitive: false,
+            default_section: DEFAULT_SECTION.to_string(),
+            fallback_section: None,
+            conditions: Vec::new(),
+            includes: false,
+            max_include_depth: 10,
+            systemd_continuation: false,
+            empty_resets: false,
+            glob_sections: false,
+            spaced_keys: false,
+            custom_dialect: None,
+            trim: TrimPolicy::Both,
+            normalization: Normalization::None,
+            key_chars: None,
+            interpolation: Interpolation::Extended,
+            env_interpolation: false,
+            max_interpolation_depth: 10,
+            max_interpolated_length: 1 << 20,
+            max_input_size: None,
+            max_line_length: None,
+            max_sections: None,
+            max_keys_per_section: None,
+            lenient: false,
+            strict: false,
+            key_migrations: Vec::new(),
+        }
+    }
+}
+
+impl ParserOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Creates options for MySQL and MariaDB option files such as `my.cnf`: `!include` and
+     * `!includedir` directives are followed, `#` comments may end a line, and options given
+     * without a value, like `skip-name-resolve`, are read as `true`.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let temp = tempfile::tempdir().unwrap();
+     * let dir = temp.path();
+     * std::fs::create_dir(dir.join("conf.d")).unwrap();
+     * std::fs::write(dir.join("conf.d/50-server.cnf"), "[mysqld]\nport = 3307\n").unwrap();
+     * std::fs::write(dir.join("conf.d/10-base.cnf"), "[mysqld]\nport = 3306\nuser = mysql\n").unwrap();
+     * std::fs::write(dir.join("conf.d/notes.txt"), "not an option file").unwrap();
+     * std::fs::write(
+     *     dir.join("my.cnf"),
+     *     "[mysqld]\nskip-name-resolve\nbind-address = 127.0.0.1 # local only\n!includedir conf.d\n",
+     * )
+     * .unwrap();
+     *
+     * let path = dir.join("my.cnf");
+     * let parser = INIParser::from_file_with_options(path.to_str().unwrap(), ParserOptions::my_cnf()).unwrap();
+     * assert_eq!(parser.get("mysqld", "port"), Some("3307"));
+     * assert_eq!(parser.get("mysqld", "user"), Some("mysql"));
+     * assert_eq!(parser.get("mysqld", "bind-address"), Some("127.0.0.1"));
+     * assert!(parser.get_bool("mysqld", "skip-name-resolve").unwrap());
+     * ```
+     */
+    pub fn my_cnf() -> Self {
+        Self::new()
+            .includes(true)
+            .inline_comments(true)
+            .valueless_keys(ValuelessKeys::True)
+    }
+
+    /**
+     * Creates options for systemd unit files such as `.service` and `.timer` files: repeated
+     * keys collect into lists that an empty assignment resets, lines continue after a trailing
+     * backslash with systemd's joining rules, only `=` separates keys from values and quotes
+     * are kept as part of the value.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[Service]\nExecStart=/usr/bin/old\nExecStart=\nExecStart=/usr/bin/app \\\n# tuned for production\n    --workers 4\nEnvironment=\"LANG=C\" TZ=UTC\n";
+     * let parser = INIParser::from_string_with_options(content, ParserOptions::systemd()).unwrap();
+     * assert_eq!(parser.get_all("Service", "ExecStart"), ["/usr/bin/app  --workers 4"]);
+     * assert_eq!(parser.get("Service", "Environment"), Some("\"LANG=C\" TZ=UTC"));
+     * ```
+     */
+    pub fn systemd() -> Self {
+        Self::new()
+            .line_continuation(true)
+            .systemd_continuation(true)
+            .quoted_values(false)
+            .colon_delimiter(false)
+            .duplicate_keys(DuplicateKeyPolicy::CollectAll)
+            .empty_resets(true)
+    }
+
+    /**
+     * Creates options for XDG Desktop Entry files such as `.desktop` files: only `#` starts a
+     * comment, only `=` separates keys from values, quotes and `;`-separated lists are kept
+     * as written, and repeated keys or groups are errors. Localized keys like `Name[fr]` are
+     * read with `INIParser::get_localized`.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[Desktop Entry]\nType=Application\nName=Files\nName[de]=Dateien\nCategories=GNOME;Utility;\n";
+     * let parser = INIParser::from_string_with_options(content, ParserOptions::desktop_entry()).unwrap();
+     * assert_eq!(parser.get("Desktop Entry", "Categories"), Some("GNOME;Utility;"));
+     * assert_eq!(parser.get_localized("Desktop Entry", "Name", "de_AT"), Some("Dateien"));
+     * ```
+     */
+    pub fn desktop_entry() -> Self {
+        Self::new()
+            .comment_prefixes(["#"])
+            .colon_delimiter(false)
+            .quoted_values(false)
+            .duplicate_keys(DuplicateKeyPolicy::Error)
+            .duplicate_sections(DuplicateSectionPolicy::Error)
+    }
+
+    /**
+     * Creates options for `.editorconfig` files: section headers are glob patterns taken
+     * literally, dots included, lookups ignore case as the specification asks for keys,
+     * section names included, and only `=` separates keys from values. The top-level `root`
+     * key ends up in the default section, see `INIParser::globals`.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "root = true\n\n[*.{js,py}]\nIndent_Style = space\n\n[Makefile]\nindent_style = tab\n";
+     * let parser = INIParser::from_string_with_options(content, ParserOptions::editorconfig()).unwrap();
+     * assert_eq!(parser.globals().unwrap().get("root"), Some("true"));
+     * assert_eq!(parser.sections().skip(1).collect::<Vec<_>>(), ["*.{js,py}", "Makefile"]);
+     * assert_eq!(parser.get("*.{js,py}", "indent_style"), Some("space"));
+     * ```
+     */
+    pub fn editorconfig() -> Self {
+        Self::new()
+            .glob_sections(true)
+            .case_insensitive(true)
+            .colon_delimiter(false)
+            .quoted_values(false)
+    }
+
+    /**
+     * Creates options for Samba's `smb.conf`: keys may contain spaces, lookups ignore case,
+     * lines continue after a trailing backslash, only `=` separates keys from values, and
+     * values are kept as written, so `%`-macros like `%U` or `%S` are left for Samba to expand.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[global]\n   workgroup = WORKGROUP\n   server string = Samba %v on %h\n   hosts allow = 127. \\\n      192.168.1.\n\n[homes]\n   path = /home/%U\n";
+     * let parser = INIParser::from_string_with_options(content, ParserOptions::smb_conf()).unwrap();
+     * assert_eq!(parser.get("global", "Server String"), Some("Samba %v on %h"));
+     * assert_eq!(parser.get("global", "hosts allow"), Some("127. 192.168.1."));
+     * assert_eq!(parser.get("homes", "path"), Some("/home/%U"));
+     * ```
+     */
+    pub fn smb_conf() -> Self {
+        Self::new()
+            .spaced_keys(true)
+            .case_insensitive(true)
+            .line_continuation(true)
+            .colon_delimiter(false)
+            .quoted_values(false)
+    }
+
+    /**
+     * Creates options bundling the comment prefixes, delimiters, duplicate policies and quoting
+     * rules of a dialect, which further builder calls can adjust.
+     *
+     * # Arguments
+     * * `dialect` - The family of INI files to read.
+     *
+     * # Returns
+     * Returns the `ParserOptions` for the dialect.
+     *
+     * ```rust
+     * use dotini::{Dialect, INIParser, ParserOptions};
+     *
+     * let content = "[core]\n\tbare\n[remote \"origin\"]\n\turl = https://example.com/repo.git ; mirror\n\tfetch = +refs/heads/main:refs/remotes/origin/main\n\tfetch = +refs/heads/dev:refs/remotes/origin/dev\n";
+     * let parser = INIParser::from_string_with_options(content, ParserOptions::dialect(Dialect::GitConfig)).unwrap();
+     * assert!(parser.get_bool("core", "Bare").unwrap());
+     * assert_eq!(parser.get("remote \"origin\"", "url"), Some("https://example.com/repo.git"));
+     * assert_eq!(parser.get_all("remote \"origin\"", "fetch").len(), 2);
+     *
+     * let parser = INIParser::from_string_with_options("[Setup]\nPath=C:\\Tools\nPATH=D:\\Other\n", ParserOptions::dialect(Dialect::Windows)).unwrap();
+     * assert_eq!(parser.get("setup", "path"), Some("C:\\Tools"));
+     * ```
+     */
+    pub fn dialect(dialect: Dialect) -> Self {
+        match dialect {
+            Dialect::GitConfig => Self::new()
+                .inline_comments(true)
+                .escapes(true)
+                .line_continuation(true)
+                .colon_delimiter(false)
+                .valueless_keys(ValuelessKeys::True)
+                .duplicate_keys(DuplicateKeyPolicy::CollectAll)
+                .case_insensitive(true),
+            Dialect::Systemd => Self::systemd(),
+            Dialect::PhpIni => Self::new().comment_prefixes([";"]).colon_delimiter(false),
+            Dialect::EditorConfig => Self::editorconfig(),
+            Dialect::Properties => Self::new()
+                .comment_prefixes(["#", "!"])
+                .line_continuation(true)
+                .quoted_values(false),
+            Dialect::Dotenv => Self::new()
+                .comment_prefixes(["#"])
+                .inline_comments(true)
+                .escapes(true)
+                .colon_delimiter(false),
+            Dialect::Windows => Self::new()
+                .comment_prefixes([";"])
+                .colon_delimiter(false)
+                .case_insensitive(true)
+                .duplicate_keys(DuplicateKeyPolicy::KeepFirst),
+            Dialect::MyCnf => Self::my_cnf(),
+            Dialect::DesktopEntry => Self::desktop_entry(),
+            Dialect::SmbConf => Self::smb_conf(),
+        }
+    }
+
+    /**
+     * Creates options for an INI variant defined outside this crate, starting from the
+     * dialect's own options and consulting its hooks while parsing.
+     *
+     * # Arguments
+     * * `dialect` - The `IniDialect` implementation.
+     *
+     * # Returns
+     * Returns the `ParserOptions` for the dialect.
+     */
+    pub fn custom_dialect(dialect: impl IniDialect + 'static) -> Self {
+        let mut options = dialect.options();
+        options.custom_dialect = Some(Arc::new(dialect));
+        options
+    }
+
+    /**
+     * Sets the order in which sections and keys are kept.
+     *
+     * ```rust
+     * use dotini::{INIParser, Order, ParserOptions};
+     *
+     * let options = ParserOptions::new().order(Order::Sorted);
+     * let mut parser = INIParser::from_string_with_options("[b]\ny=1\nx=2\n[a]\n", options).unwrap();
+     * parser.set("a", "z", "3");
+     * parser.set("a", "c", "4");
+     * assert_eq!(parser.sections().collect::<Vec<_>>(), ["a", "b"]);
+     * assert_eq!(parser.keys("a").collect::<Vec<_>>(), ["c", "z"]);
+     * assert_eq!(parser.keys("b").collect::<Vec<_>>(), ["x", "y"]);
+     * ```
+     */
+    pub fn order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+
+    /**
+     * Sets the markers that start a comment line, `;` and `#` by default.
+     * A line is a comment when its first non-blank characters are one of the prefixes.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let options = ParserOptions::new().comment_prefixes(["//", ";"]);
+     * let parser = INIParser::from_string_with_options("// generated\n[db]\nport=5432\n", options).unwrap();
+     * assert_eq!(parser.get("db", "port"), Some("5432"));
+     *
+     * let options = ParserOptions::new().comment_prefixes([";"]);
+     * assert!(INIParser::from_string_with_options("# not a comment\n", options).is_err());
+     * ```
+     */
+    pub fn comment_prefixes<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.comment_prefixes = prefixes
+            .into_iter()
+            .map(Into::into)
+            .filter(|prefix| !prefix.is_empty())
+            .collect();
+        self
+    }
+
+    /**
+     * Strips comments that follow a value on the same line, such as `timeout = 30 ; seconds`.
+     * A comment prefix starts an inline comment when it begins the value or follows
+     * whitespace; a backslash before one of its characters, as in `\;`, keeps it literally.
+     * Disabled by default, so values are read up to the end of the line.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "timeout = 30 ; seconds\npattern = a\\;b\ncolor = #fff\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("untagged", "timeout"), Some("30 ; seconds"));
+     *
+     * let options = ParserOptions::new().inline_comments(true);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("untagged", "timeout"), Some("30"));
+     * assert_eq!(parser.get("untagged", "pattern"), Some("a;b"));
+     * assert_eq!(parser.get("untagged", "color"), Some(""));
+     * ```
+     */
+    pub fn inline_comments(mut self, inline_comments: bool) -> Self {
+        self.inline_comments = inline_comments;
+        self
+    }
+
+    /**
+     * Removes the double or single quotes around a value, keeping the text between them
+     * verbatim, so values can have surrounding whitespace or contain comment markers.
+     * Enabled by default; when disabled, quotes are part of the value.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "name = \"John  Doe \"\nmotto = 'semi;colons #kept'\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("untagged", "name"), Some("John  Doe "));
+     * assert_eq!(parser.get("untagged", "motto"), Some("semi;colons #kept"));
+     *
+     * let options = ParserOptions::new().quoted_values(false);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("untagged", "name"), Some("\"John  Doe \""));
+     * ```
+     */
+    pub fn quoted_values(mut self, quoted_values: bool) -> Self {
+        self.quoted_values = quoted_values;
+        self
+    }
+
+    /**
+     * Interprets the escape sequences `\n`, `\t`, `\r`, `\\`, `\"`, `\'`, `\;` and `\#` inside
+     * double-quoted values, along with the Unicode escapes `\uXXXX` (including surrogate pairs)
+     * and `\x{...}`. Single-quoted and unquoted values are never unescaped, and unknown or
+     * malformed sequences are kept as they are. Disabled by default, since Windows paths are
+     * often written with single backslashes.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = r#"greeting = "Hello,\n\t\"world\"" "#;
+     * let options = ParserOptions::new().escapes(true);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("untagged", "greeting"), Some("Hello,\n\t\"world\""));
+     *
+     * let content = r#"name = "Caf\u00e9 \x{1F375} \uD83D\uDE00" "#;
+     * let parser = INIParser::from_string_with_options(content, ParserOptions::new().escapes(true)).unwrap();
+     * assert_eq!(parser.get("untagged", "name"), Some("Caf\u{e9} \u{1F375} \u{1F600}"));
+     * ```
+     */
+    pub fn escapes(mut self, escapes: bool) -> Self {
+        self.escapes = escapes;
+        self
+    }
+
+    /**
+     * Continues a value onto the next line when its line ends with a backslash. The pieces are
+     * joined without the backslash, the line break and the indentation of the next line; a
+     * doubled backslash at the end of a line is not a continuation. Disabled by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[java]\nclasspath = lib/a.jar:\\\n    lib/b.jar:\\\n    lib/c.jar\n";
+     * let options = ParserOptions::new().line_continuation(true);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("java", "classpath"), Some("lib/a.jar:lib/b.jar:lib/c.jar"));
+     * ```
+     */
+    pub fn line_continuation(mut self, line_continuation: bool) -> Self {
+        self.line_continuation = line_continuation;
+        self
+    }
+
+    /// Joins backslash-continued lines the way systemd does: the backslash is replaced by a
+    /// space, and comment lines inside the continuation are skipped. Only has an effect
+    /// together with `ParserOptions::line_continuation`.
+    pub fn systemd_continuation(mut self, systemd_continuation: bool) -> Self {
+        self.systemd_continuation = systemd_continuation;
+        self
+    }
+
+    /// Reads everything between the opening bracket and the last closing bracket of a header
+    /// line as the section name, so glob patterns like `[*.{js,ts}]` or `[lib/**/[a-z]*.rs]`
+    /// are taken literally. Subsections, parents and conditions are then not recognized.
+    pub fn glob_sections(mut self, glob_sections: bool) -> Self {
+        self.glob_sections = glob_sections;
+        self
+    }
+
+    /// Makes an empty assignment such as `ExecStart=` discard the values a key collected so
+    /// far under `DuplicateKeyPolicy::CollectAll`, as in systemd unit files.
+    pub fn empty_resets(mut self, empty_resets: bool) -> Self {
+        self.empty_resets = empty_resets;
+        self
+    }
+
+    /// Allows keys made of several words separated by spaces, like Samba's `workgroup name`.
+    /// Runs of whitespace inside such a key are read as a single space, and the writer leaves
+    /// these keys unquoted.
+    pub fn spaced_keys(mut self, spaced_keys: bool) -> Self {
+        self.spaced_keys = spaced_keys;
+        self
+    }
+
+    /**
+     * Sets which references `INIParser::get_interpolated` and `INIParser::interpolate`
+     * resolve. Plain getters always return values as written.
+     *
+     * ```rust
+     * use dotini::{INIParser, Interpolation, ParserOptions};
+     *
+     * std::env::set_var("DOTINI_DOC_APPDATA", r"C:\Users\demo\AppData\Roaming");
+     * let content = "[Paths]\nProfile=%DOTINI_DOC_APPDATA%\\Demo\nUnknown=%DOTINI_DOC_UNSET%\\x\nDiscount=50%% off\n";
+     * let options = ParserOptions::new().interpolation(Interpolation::Windows);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(
+     *     parser.get_interpolated("Paths", "Profile").unwrap().as_deref(),
+     *     Some(r"C:\Users\demo\AppData\Roaming\Demo")
+     * );
+     * assert_eq!(parser.get_interpolated("Paths", "Unknown").unwrap().as_deref(), Some(r"%DOTINI_DOC_UNSET%\x"));
+     * assert_eq!(parser.get_interpolated("Paths", "Discount").unwrap().as_deref(), Some("50% off"));
+     * ```
+     */
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /**
+     * Lets `${ENV:NAME}` and `${env.NAME}` references resolve to the environment variable
+     * `NAME` during interpolation, before any section named `ENV` is considered. Referencing
+     * a variable that is not set is an `InIParseError::InterpolationError`.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * std::env::set_var("DOTINI_DOC_HOME", "/home/demo");
+     * let content = "[paths]\ncache = ${ENV:DOTINI_DOC_HOME}/.cache\nlogs = ${env.DOTINI_DOC_HOME}/logs\n";
+     * let options = ParserOptions::new().env_interpolation(true);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get_interpolated("paths", "cache").unwrap().as_deref(), Some("/home/demo/.cache"));
+     * assert_eq!(parser.get_interpolated("paths", "logs").unwrap().as_deref(), Some("/home/demo/logs"));
+     *
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert!(parser.get_interpolated("paths", "cache").is_err());
+     * ```
+     */
+    pub fn env_interpolation(mut self, env_interpolation: bool) -> Self {
+        self.env_interpolation = env_interpolation;
+        self
+    }
+
+    /**
+     * Sets how many levels of references interpolation follows before giving up with an
+     * `InIParseError::InterpolationError`, 10 by default like Python's `configparser`.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[chain]\na = ${b}\nb = ${c}\nc = end\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get_interpolated("chain", "a").unwrap().as_deref(), Some("end"));
+     *
+     * let options = ParserOptions::new().max_interpolation_depth(1);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get_interpolated("chain", "b").unwrap().as_deref(), Some("end"));
+     * assert!(parser.get_interpolated("chain", "a").is_err());
+     * ```
+     */
+    pub fn max_interpolation_depth(mut self, depth: usize) -> Self {
+        self.max_interpolation_depth = depth;
+        self
+    }
+
+    /**
+     * Sets how many bytes an interpolated value may grow to, 1 MiB by default, so that a small
+     * config referencing the same keys many times over cannot expand into gigabytes. A value
+     * growing past it is an `InIParseError::InterpolationTooLarge`.
+     *
+     * ```rust
+     * use dotini::{INIParser, InIParseError, ParserOptions};
+     *
+     * let content = "[x]\na = 0123456789\nb = ${a}${a}${a}${a}\nc = ${b}${b}${b}${b}\n";
+     * let options = ParserOptions::new().max_interpolated_length(100);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get_interpolated("x", "b").unwrap().unwrap().len(), 40);
+     * let err = parser.get_interpolated("x", "c").unwrap_err();
+     * assert!(matches!(err, InIParseError::InterpolationTooLarge { limit: 100, .. }));
+     * assert_eq!(err.to_string(), "interpolated value of x:c exceeds the limit of 100 bytes");
+     * ```
+     */
+    pub fn max_interpolated_length(mut self, length: usize) -> Self {
+        self.max_interpolated_length = length;
+        self
+    }
+
+    /**
+     * Restricts the characters of parsed keys, for configs that feed systems with strict naming
+     * rules. A key with any other character is a parse error pointing at that character.
+     *
+     * # Arguments
+     * * `allowed` - Returns `true` for the characters keys may contain.
+     *
+     * ```rust
+     * use dotini::{INIParser, InIParseError, ParserOptions};
+     *
+     * let options = ParserOptions::new()
+     *     .key_chars(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+     * assert!(INIParser::from_string_with_options("[app]\nlog.level = info\n", options.clone()).is_ok());
+     * let err = INIParser::from_string_with_options("[app]\n\"log level\" = info\n", options).unwrap_err();
+     * assert!(matches!(err, InIParseError::UnsuccessfulParse(err) if (err.line, err.column) == (2, 5)));
+     * ```
+     */
+    pub fn key_chars(mut self, allowed: fn(char) -> bool) -> Self {
+        self.key_chars = Some(allowed);
+        self
+    }
+
+    /**
+     * Appends lines indented deeper than a property to its value, separated by `\n`, as
+     * Python's `configparser` does. The indented lines lose their indentation and the first
+     * line is trimmed like any value, following `ParserOptions::trim`; a blank or comment line
+     * ends the value. Disabled by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions, TrimPolicy};
+     *
+     * let content = "[testenv]\ndeps =\n    pytest\n    coverage\ncommands = pytest\n";
+     * let options = ParserOptions::new().multiline_values(true);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("testenv", "deps"), Some("\npytest\ncoverage"));
+     * assert_eq!(parser.get("testenv", "commands"), Some("pytest"));
+     *
+     * let options = ParserOptions::new()
+     *     .multiline_values(true)
+     *     .trim(TrimPolicy::KeysOnly);
+     * let parser = INIParser::from_string_with_options("[a]\nmotd =  hi\n    there\n", options);
+     * assert_eq!(parser.unwrap().get("a", "motd"), Some("  hi\nthere"));
+     * ```
+     */
+    pub fn multiline_values(mut self, multiline_values: bool) -> Self {
+        self.multiline_values = multiline_values;
+        self
+    }
+
+    /**
+     * Accepts `key: value` as well as `key = value`. Enabled by default; when disabled, a `:`
+     * after a key is an error. Either way, a property is split at its first delimiter.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let parser = INIParser::from_string("[smtp]\nhost: mail.example.com\nport = 25\n").unwrap();
+     * assert_eq!(parser.get("smtp", "host"), Some("mail.example.com"));
+     *
+     * let options = ParserOptions::new().colon_delimiter(false);
+     * assert!(INIParser::from_string_with_options("host: mail.example.com\n", options).is_err());
+     * ```
+     */
+    pub fn colon_delimiter(mut self, colon_delimiter: bool) -> Self {
+        self.colon_delimiter = colon_delimiter;
+        self
+    }
+
+    /**
+     * Accepts properties with nothing after the delimiter, such as `key =`, reading them as
+     * an empty string. Enabled by default; when disabled, such lines are errors, which catches
+     * values that were forgotten. Under `ParserOptions::strict` they are accepted but reported
+     * as `WarningKind::EmptyValue` warnings. An explicitly quoted `""` is always accepted.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions, WarningKind};
+     *
+     * let parser = INIParser::from_string("[db]\npassword =\n").unwrap();
+     * assert_eq!(parser.get("db", "password"), Some(""));
+     *
+     * let options = ParserOptions::new().empty_values(false);
+     * assert!(INIParser::from_string_with_options("[db]\npassword =\n", options.clone()).is_err());
+     * assert!(INIParser::from_string_with_options("[db]\npassword = \"\"\n", options).is_ok());
+     *
+     * let options = ParserOptions::new().strict(true);
+     * let content = "[db]\npassword =\nuser = \"\"\n";
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * let warnings: Vec<_> = parser.warnings().iter().map(|warning| (warning.kind, warning.line)).collect();
+     * assert_eq!(warnings, [(WarningKind::EmptyValue, 2)]);
+     * assert!(INIParser::from_string("[db]\npassword =\n").unwrap().warnings().is_empty());
+     * ```
+     */
+    pub fn empty_values(mut self, empty_values: bool) -> Self {
+        self.empty_values = empty_values;
+        self
+    }
+
+    /**
+     * Normalizes section and key names when they are parsed, inserted and looked up, so names
+     * that look the same compare equal. Names are stored in their normalized form.
+     *
+     * ```rust
+     * use dotini::{INIParser, Normalization, ParserOptions};
+     *
+     * let content = "[font \"Caf\u{e9}\"]\n\"St\u{e4}rke\" = 3\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("font \"Cafe\u{301}\"", "Sta\u{308}rke"), None);
+     *
+     * let options = ParserOptions::new().normalize_names(Normalization::Nfc);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("font \"Cafe\u{301}\"", "Sta\u{308}rke"), Some("3"));
+     *
+     * let options = ParserOptions::new().normalize_names(Normalization::NfcCaseFold);
+     * let mut parser = INIParser::from_string_with_options(content, options).unwrap();
+     * parser.set("FONT \"CAF\u{c9}\"", "Gr\u{d6}\u{df}e", "L");
+     * assert_eq!(parser.sections().collect::<Vec<_>>(), ["font \"caf\u{e9}\""]);
+     * let font = parser.section("Font \"Cafe\u{301}\"").unwrap();
+     * assert_eq!(font.get("gr\u{f6}\u{df}e"), Some("L"));
+     * assert_eq!(font.get("ST\u{c4}RKE"), Some("3"));
+     * ```
+     */
+    pub fn normalize_names(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /**
+     * Sets how whitespace around keys and values is treated, for applications that need values
+     * preserved exactly. The continuation lines of values spanning several lines still lose
+     * their indentation.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions, TrimPolicy};
+     *
+     * let content = "[banner]\n  prefix =  >> \n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("banner", "prefix"), Some(">>"));
+     *
+     * let options = ParserOptions::new().trim(TrimPolicy::KeysOnly);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("banner", "prefix"), Some("  >> "));
+     *
+     * let options = ParserOptions::new().trim(TrimPolicy::Verbatim);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("banner", "  prefix "), Some("  >> "));
+     * ```
+     */
+    pub fn trim(mut self, trim: TrimPolicy) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /**
+     * Sets how bare keys such as `skip-name-resolve` are read, as in MySQL's `my.cnf`.
+     * They are rejected by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions, ValuelessKeys};
+     *
+     * let content = "[mysqld]\nskip-name-resolve\nport = 3306\n";
+     * assert!(INIParser::from_string(content).is_err());
+     *
+     * let options = ParserOptions::new().valueless_keys(ValuelessKeys::True);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert!(parser.get_bool("mysqld", "skip-name-resolve").unwrap());
+     * ```
+     */
+    pub fn valueless_keys(mut self, valueless_keys: ValuelessKeys) -> Self {
+        self.valueless_keys = valueless_keys;
+        self
+    }
+
+    /**
+     * Sets what happens when a key is repeated within a section, which silently keeps the
+     * last value by default.
+     *
+     * ```rust
+     * use dotini::{DuplicateKeyPolicy, INIParser, ParserOptions};
+     *
+     * let content = "[server]\nport = 80\nport = 8080\n";
+     * assert_eq!(INIParser::from_string(content).unwrap().get("server", "port"), Some("8080"));
+     *
+     * let options = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::KeepFirst);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("server", "port"), Some("80"));
+     *
+     * let options = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+     * assert!(INIParser::from_string_with_options(content, options).is_err());
+     * ```
+     */
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /**
+     * Sets what happens when a section header is repeated, which reopens the section and
+     * appends the properties of the new block to it by default.
+     *
+     * ```rust
+     * use dotini::{DuplicateSectionPolicy, INIParser, ParserOptions};
+     *
+     * let content = "[server]\nhost = localhost\n[client]\nretries = 3\n[server]\nport = 8080\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("server", "port"), Some("8080"));
+     *
+     * let options = ParserOptions::new().duplicate_sections(DuplicateSectionPolicy::Error);
+     * assert!(INIParser::from_string_with_options(content, options).is_err());
+     * ```
+     */
+    pub fn duplicate_sections(mut self, policy: DuplicateSectionPolicy) -> Self {
+        self.duplicate_sections = policy;
+        self
+    }
+
+    /**
+     * Makes section and key lookups ignore case, like the Windows profile API, lowercasing
+     * letters beyond ASCII as well.
+     * Names keep the casing they were first written with, both in memory and on output, and
+     * headers or keys that only differ in case are treated as repeats of the first one.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[Boot]\nTimeout=30\n[boot]\nDefault=C\n";
+     * let options = ParserOptions::new().case_insensitive(true);
+     * let mut parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("BOOT", "timeout"), Some("30"));
+     * parser.set("boot", "TIMEOUT", "5");
+     * assert_eq!(parser.to_ini_string().unwrap(), "[Boot]\nTimeout = 5\nDefault = C\n");
+     *
+     * let content = "[font \"Caf\u{e9}\"]\n\"\u{c4}rger\" = 1\n[FONT \"CAF\u{c9}\"]\n\"\u{e4}RGER\" = 2\n";
+     * let options = ParserOptions::new().case_insensitive(true);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.sections().collect::<Vec<_>>(), ["font \"Caf\u{e9}\""]);
+     * assert_eq!(parser.section("Font \"caf\u{c9}\"").unwrap().len(), 1);
+     * assert_eq!(parser.get("font \"caf\u{e9}\"", "\u{e4}rger"), Some("2"));
+     * ```
+     */
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /**
+     * Sets the name of the section holding the properties that appear before the first
+     * section header, which is `DEFAULT_SECTION` by default. That section is written back
+     * without a header.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "version = 2\n\n[server]\nport = 80\n";
+     * let options = ParserOptions::new().default_section("");
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("", "version"), Some("2"));
+     * assert_eq!(parser.sections().collect::<Vec<_>>(), ["", "server"]);
+     * assert_eq!(parser.to_ini_string().unwrap(), content);
+     * ```
+     */
+    pub fn default_section(mut self, name: impl Into<String>) -> Self {
+        self.default_section = name.into();
+        self
+    }
+
+    /**
+     * Resolves keys missing from a section from another section, like the `[DEFAULT]` section
+     * of Python's `configparser`. The fallback applies to the value getters of `INIParser`,
+     * such as `get`, `contains_key` and the typed getters, for sections that exist; it is
+     * disabled by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[DEFAULT]\ntimeout = 30\n[api]\nhost = a\n[db]\ntimeout = 5\n";
+     * let options = ParserOptions::new().fallback_section(Some("DEFAULT"));
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("api", "timeout"), Some("30"));
+     * assert_eq!(parser.get("db", "timeout"), Some("5"));
+     * assert_eq!(parser.get("missing", "timeout"), None);
+     * assert_eq!(INIParser::from_string(content).unwrap().get("api", "timeout"), None);
+     * ```
+     */
+    pub fn fallback_section(mut self, name: Option<impl Into<String>>) -> Self {
+        self.fallback_section = name.map(Into::into);
+        self
+    }
+
+    /// Returns `true` if a line is a comment.
+    pub(crate) fn is_comment(&self, line: &str) -> bool {
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        self.comment_prefixes
+            .iter()
+            .any(|prefix| trimmed.starts_with(prefix.as_str()))
+            || self
+                .custom_dialect
+                .as_ref()
+                .is_some_and(|dialect| dialect.is_comment(line))
+    }
+
+    /// Applies the key normalization of a custom dialect and `ParserOptions::normalize_names`.
+    pub(crate) fn normalize_key(&self, key: String) -> String {
+        let key = match &self.custom_dialect {
+            Some(dialect) => dialect.normalize_key(key),
+            None => key,
+        };
+        self.normalize(&key).into_owned()
+    }
+
+    /// Normalizes a section or key name as `ParserOptions::normalize_names` asks for.
+    pub(crate) fn normalize<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        section::normalize(name, self.normalization)
+    }
+
+    /// Applies the escape rules of a custom dialect.
+    pub(crate) fn unescape(&self, value: String) -> String {
+        match &self.custom_dialect {
+            Some(dialect) => dialect.unescape(value),
+            None => value,
+        }
+    }
+
+    /// Returns `true` if a comment prefix starts with the given character.
+    pub(crate) fn starts_comment(&self, c: char) -> bool {
+        self.comment_prefixes
+            .iter()
+            .any(|prefix| prefix.starts_with(c))
+    }
+
+    /**
+     * Declares a fact about the environment, such as `os=windows`, and enables conditional
+     * section headers like `[paths?os=windows&arch=x86_64]`. A conditional section is only
+     * read when every one of its conditions matches a declared fact; it then counts as a
+     * repeated header of the name before the `?`, following `ParserOptions::duplicate_sections`.
+     * Sections whose conditions do not hold are skipped along with their properties.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let content = "[paths]\ncache = /var/cache/app\n[paths?os=windows]\ncache = C:\\cache\n";
+     * let options = ParserOptions::new().condition("os", "windows");
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("paths", "cache"), Some("C:\\cache"));
+     *
+     * let options = ParserOptions::new().condition("os", "linux");
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("paths", "cache"), Some("/var/cache/app"));
+     *
+     * assert!(INIParser::from_string(content).is_err());
+     * ```
+     */
+    pub fn condition(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.conditions.push((name.into(), value.into()));
+        self
+    }
+
+    /// Declares the `os`, `family` and `arch` facts of the running platform, as found in
+    /// `std::env::consts`, for use in conditional section headers.
+    pub fn platform_conditions(self) -> Self {
+        use std::env::consts::{ARCH, FAMILY, OS};
+        self.condition("os", OS)
+            .condition("family", FAMILY)
+            .condition("arch", ARCH)
+    }
+
+    /**
+     * Enables `!include path` directives, which read another file in place of the directive,
+     * and `!includedir path` directives, which read the option files of a directory in name
+     * order like MySQL does.
+     * Relative paths are resolved against the directory of the including file, or against
+     * the working directory when parsing a string. The included file starts in the default
+     * section and shares the duplicate key and section policies of the including one, which
+     * resumes in its own section afterwards. Include cycles are reported as errors.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let temp = tempfile::tempdir().unwrap();
+     * let dir = temp.path();
+     * std::fs::write(dir.join("db.ini"), "[db]\nhost = localhost\n").unwrap();
+     * std::fs::write(dir.join("app.ini"), "[app]\nname = demo\n!include db.ini\n").unwrap();
+     * std::fs::write(dir.join("loop.ini"), "!include loop.ini\n").unwrap();
+     *
+     * let options = ParserOptions::new().includes(true);
+     * let path = dir.join("app.ini");
+     * let parser = INIParser::from_file_with_options(path.to_str().unwrap(), options.clone()).unwrap();
+     * assert_eq!(parser.get("db", "host"), Some("localhost"));
+     * assert_eq!(parser.get("app", "name"), Some("demo"));
+     *
+     * let path = dir.join("loop.ini");
+     * assert!(INIParser::from_file_with_options(path.to_str().unwrap(), options).is_err());
+     * assert!(INIParser::from_file(dir.join("app.ini").to_str().unwrap()).is_err());
+     * ```
+     */
+    pub fn includes(mut self, includes: bool) -> Self {
+        self.includes = includes;
+        self
+    }
+
+    /// Sets how deeply `!include` directives may be nested, 10 by default.
+    pub fn max_include_depth(mut self, depth: usize) -> Self {
+        self.max_include_depth = depth;
+        self
+    }
+
+    /**
+     * Limits the size in bytes of the text to parse, so that services reading untrusted
+     * configs can reject oversized input up front. Each included file is checked on its own.
+     * There is no limit by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, InIParseError, ParserOptions};
+     *
+     * let options = ParserOptions::new().max_input_size(16);
+     * let err = INIParser::from_string_with_options("[server]\nhost = example.com\n", options).unwrap_err();
+     * assert!(matches!(err, InIParseError::InputTooLarge { size: 28, limit: 16 }));
+     * ```
+     */
+    pub fn max_input_size(mut self, bytes: usize) -> Self {
+        self.max_input_size = Some(bytes);
+        self
+    }
+
+    /**
+     * Limits the length in bytes of a line, counting the lines that continue it, without its
+     * line terminator. There is no limit by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, InIParseError, ParserOptions};
+     *
+     * let content = format!("[server]\nhost = {}\n", "x".repeat(100));
+     * let options = ParserOptions::new().max_line_length(64);
+     * let err = INIParser::from_string_with_options(&content, options).unwrap_err();
+     * assert!(matches!(err, InIParseError::LineTooLong { line: 2, length: 107, limit: 64 }));
+     *
+     * // A long line is not skipped like a malformed one.
+     * let options = ParserOptions::new().max_line_length(10).lenient(true);
+     * let err = INIParser::from_string_with_options("[a]\nkey = 0123456789abc\n", options).unwrap_err();
+     * assert!(matches!(err, InIParseError::LineTooLong { line: 2, .. }));
+     * ```
+     */
+    pub fn max_line_length(mut self, bytes: usize) -> Self {
+        self.max_line_length = Some(bytes);
+        self
+    }
+
+    /**
+     * Limits how many sections the config may hold, counting the default section once it has
+     * properties and every block of a section repeated under `DuplicateSectionPolicy::Separate`.
+     * There is no limit by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, InIParseError, ParserOptions};
+     *
+     * let options = ParserOptions::new().max_sections(2);
+     * assert!(INIParser::from_string_with_options("[a]\n[b]\n[a]\n", options.clone()).is_ok());
+     * let err = INIParser::from_string_with_options("[a]\n[b]\n[c]\n", options).unwrap_err();
+     * assert!(matches!(err, InIParseError::TooManySections { limit: 2 }));
+     * ```
+     */
+    pub fn max_sections(mut self, sections: usize) -> Self {
+        self.max_sections = Some(sections);
+        self
+    }
+
+    /**
+     * Limits how many distinct keys a section may hold. Repeated keys count once. There is no
+     * limit by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, InIParseError, ParserOptions};
+     *
+     * let options = ParserOptions::new().max_keys_per_section(2);
+     * assert!(INIParser::from_string_with_options("[a]\nx = 1\ny = 2\nx = 3\n", options.clone()).is_ok());
+     * let err = INIParser::from_string_with_options("[a]\nx = 1\ny = 2\nz = 3\n", options).unwrap_err();
+     * assert!(matches!(err, InIParseError::TooManyKeys { section, limit: 2 } if section == "a"));
+     * ```
+     */
+    pub fn max_keys_per_section(mut self, keys: usize) -> Self {
+        self.max_keys_per_section = Some(keys);
+        self
+    }
+
+    /**
+     * Skips the lines that cannot be read, such as malformed lines, duplicates rejected by a
+     * policy or failed includes, instead of failing the whole parse. The skipped lines are
+     * reported by `INIParser::errors`, and a document keeps them as they are. Exceeding one of
+     * the limits still fails the parse.
+     */
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /**
+     * Sets what happens to properties that appear before the first section header, which are
+     * stored under the default section by default.
+     *
+     * ```rust
+     * use dotini::{INIParser, OrphanPolicy, ParserOptions};
+     *
+     * let content = "debug = true\n[server]\nport = 8080\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * assert_eq!(parser.get("untagged", "debug"), Some("true"));
+     *
+     * let options = ParserOptions::new().orphan_properties(OrphanPolicy::Ignore);
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.sections().collect::<Vec<_>>(), ["server"]);
+     *
+     * let options = ParserOptions::new().orphan_properties(OrphanPolicy::Error);
+     * assert!(INIParser::from_string_with_options(content, options).is_err());
+     * ```
+     */
+    pub fn orphan_properties(mut self, policy: OrphanPolicy) -> Self {
+        self.orphan_properties = policy;
+        self
+    }
+
+    /**
+     * Rejects what is otherwise accepted silently: repeated keys and sections whatever the
+     * duplicate policies, properties before the first section header, text after the closing
+     * quote of a quoted value and, with `ParserOptions::escapes`, unknown escape sequences such
+     * as `\q`. Keys like `list[]` may still repeat. Properties with an empty value, such as
+     * `key =`, are reported as warnings, see `ParserOptions::empty_values`.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions};
+     *
+     * let strict = || ParserOptions::new().strict(true).escapes(true);
+     * let content = "[server]\nhost = \"localhost\"\nicon = \"\\uD83D\\uDE80\"\nports[] = 80\nports[] = 443\n";
+     * assert!(INIParser::from_string_with_options(content, strict()).is_ok());
+     *
+     * for content in [
+     *     "[a]\nx = 1\nx = 2\n",
+     *     "[a]\n[b]\n[a]\n",
+     *     "x = 1\n[a]\n",
+     *     "[a]\nx = \"quoted\" extra\n",
+     *     "[a]\nx = \"C:\\qux\"\n",
+     * ] {
+     *     assert!(INIParser::from_string(content).is_ok());
+     *     assert!(INIParser::from_string_with_options(content, strict()).is_err());
+     * }
+     *
+     * // Each line of a multi-line value is checked as well.
+     * let content = "[a]\nx = \"one\"\n    \"two\" extra\n";
+     * let err = INIParser::from_string_with_options(content, strict().multiline_values(true));
+     * assert!(err.unwrap_err().to_string().starts_with("parse error at 3:11"));
+     * ```
+     */
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /**
+     * Marks a key as deprecated: it is still read as written, but each occurrence is reported
+     * as a `WarningKind::Deprecated` warning.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions, WarningKind};
+     *
+     * let options = ParserOptions::new().deprecated_key("server", "legacy_mode");
+     * let parser = INIParser::from_string_with_options("[server]\nlegacy_mode = on\n", options).unwrap();
+     * assert_eq!(parser.get("server", "legacy_mode"), Some("on"));
+     * assert_eq!(parser.warnings()[0].kind, WarningKind::Deprecated);
+     * ```
+     */
+    pub fn deprecated_key(mut self, section: impl Into<String>, key: impl Into<String>) -> Self {
+        self.key_migrations.push(KeyMigration {
+            section: section.into(),
+            key: key.into(),
+            to: None,
+        });
+        self
+    }
+
+    /**
+     * Registers a renamed key, given as `(section, key)` pairs. A property written under the
+     * old name is read as if it had been written under the new one, with a
+     * `WarningKind::Deprecated` warning, and getters asked for the old name look up the new
+     * one. When both names are written, the one written last wins as for a repeated key.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions, WarningKind};
+     *
+     * let options = ParserOptions::new().migrate_key(("old", "timeout"), ("network", "timeout_secs"));
+     * let content = "[old]\ntimeout = 30\n[network]\nretries = 3\n";
+     * let parser = INIParser::from_string_with_options(content, options).unwrap();
+     * assert_eq!(parser.get("network", "timeout_secs"), Some("30"));
+     * assert_eq!(parser.get("old", "timeout"), Some("30"));
+     * assert!(parser.section("old").unwrap().is_empty());
+     *
+     * let warning = &parser.warnings()[0];
+     * assert_eq!((warning.kind, warning.line), (WarningKind::Deprecated, 2));
+     * assert_eq!(
+     *     warning.message,
+     *     "key `timeout` in section `old` is deprecated, use `timeout_secs` in section `network`"
+     * );
+     * ```
+     */
+    pub fn migrate_key(
+        mut self,
+        from: (impl Into<String>, impl Into<String>),
+        to: (impl Into<String>, impl Into<String>),
+    ) -> Self {
+        self.key_migrations.push(KeyMigration {
+            section: from.0.into(),
+            key: from.1.into(),
+            to: Some((to.0.into(), to.1.into())),
+        });
+        self
+    }
+
+    /// Returns the registration of a deprecated or renamed key, if any.
+    pub(crate) fn migration(&self, section: &str, key: &str) -> Option<&KeyMigration> {
+        let same = |a: &str, b: &str| {
+            let (a, b) = (self.normalize(a), self.normalize(b));
+            section::same_name(&a, &b, self.case_insensitive)
+        };
+        self.key_migrations
+            .iter()
+            .find(|migration| same(&migration.section, section) && same(&migration.key, key))
+    }
+
+    /// Returns `true` if a condition of a section header matches a declared fact.
+    pub(crate) fn holds(&self, name: &str, value: &str) -> bool {
+        self.conditions
+            .iter()
+            .any(|(fact, fact_value)| fact == name && fact_value == value)
+    }
+
+    /// Returns `true` if sections and keys are kept sorted.
+    pub(crate) fn sorted(&self) -> bool {
+        self.order == Order::Sorted
+    }
+}
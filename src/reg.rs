@@ -0,0 +1,307 @@
+use crate::section::find_key;
+use crate::{decode, lexer, INIParserResult, InIParseError};
+use indexmap::IndexMap;
+use std::fs;
+
+/// A typed value of a Windows Registry export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegValue {
+    /// A `REG_SZ` string, written as `"text"`.
+    String(String),
+    /// A `REG_EXPAND_SZ` string holding unexpanded `%VARIABLES%`, written as `hex(2):`.
+    ExpandString(String),
+    /// A `REG_MULTI_SZ` list of strings, written as `hex(7):`.
+    MultiString(Vec<String>),
+    /// A `REG_DWORD` number, written as `dword:0000002a`.
+    Dword(u32),
+    /// A `REG_QWORD` number, written as `hex(b):`.
+    Qword(u64),
+    /// `REG_BINARY` data, written as `hex:`.
+    Binary(Vec<u8>),
+    /// Data of any other registry type, written as `hex(kind):`.
+    Other { kind: u32, data: Vec<u8> },
+    /// A value the import removes, written as `-`.
+    Delete,
+}
+
+/// A key of a Windows Registry export together with its values.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RegKey {
+    deleted: bool,
+    values: IndexMap<String, RegValue>,
+}
+
+impl RegKey {
+    /// Returns `true` if the import removes the key, written as `[-HKEY_...]`.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+
+    /// Returns a value of the key by name. The default value, written as `@`, has an empty
+    /// name.
+    pub fn get(&self, name: &str) -> Option<&RegValue> {
+        find_key(&self.values, name, true).map(|index| &self.values[index])
+    }
+
+    /// Returns an iterator over the `(name, value)` pairs of the key.
+    pub fn values(&self) -> impl Iterator<Item = (&str, &RegValue)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+/// A parsed Windows Registry export, as written by `regedit` into `.reg` files.
+///
+/// Registry exports are INI-shaped, but their key paths contain backslashes and spaces and
+/// their values are typed, so they are read into a dedicated structure. Key and value names
+/// are looked up without regard to case, like the registry does.
+///
+/// ```rust
+/// use dotini::{RegFile, RegValue};
+///
+/// let content = r#"Windows Registry Editor Version 5.00
+///
+/// [HKEY_CURRENT_USER\Software\Demo App]
+/// @="default"
+/// "Path"="C:\\Program Files\\Demo"
+/// "Retries"=dword:00000003
+/// "Key"=hex:de,ad,\
+///   be,ef
+/// "Obsolete"=-
+///
+/// [-HKEY_CURRENT_USER\Software\Old App]
+/// "#;
+/// let reg = RegFile::parse(content).unwrap();
+/// assert_eq!(reg.version(), "Windows Registry Editor Version 5.00");
+/// let app = reg.key(r"HKEY_CURRENT_USER\Software\Demo App").unwrap();
+/// assert_eq!(app.get(""), Some(&RegValue::String("default".to_string())));
+/// assert_eq!(app.get("path"), Some(&RegValue::String(r"C:\Program Files\Demo".to_string())));
+/// assert_eq!(app.get("Retries"), Some(&RegValue::Dword(3)));
+/// assert_eq!(app.get("Key"), Some(&RegValue::Binary(vec![0xde, 0xad, 0xbe, 0xef])));
+/// assert_eq!(app.get("Obsolete"), Some(&RegValue::Delete));
+/// assert!(reg.key(r"HKEY_CURRENT_USER\Software\Old App").unwrap().is_deleted());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegFile {
+    version: String,
+    keys: IndexMap<String, RegKey>,
+}
+
+impl RegFile {
+    /**
+     * Parses the text of a Registry export.
+     *
+     * # Arguments
+     * * `content` - The text of the export, starting with its header line.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the `RegFile`, or `InIParseError::UnsuccessfulParse`
+     * pointing at the first line that is not valid Registry export syntax.
+     */
+    pub fn parse(content: &str) -> INIParserResult<Self> {
+        let mut lines =
+            logical_lines(content).filter(|(_, line)| !line.is_empty() && !line.starts_with(';'));
+        let version = match lines.next() {
+            Some((_, line))
+                if line == "REGEDIT4" || line.starts_with("Windows Registry Editor") =>
+            {
+                line
+            }
+            Some((start, _)) => {
+                return Err(lexer::error_at(
+                    content,
+                    start,
+                    "expected a `Windows Registry Editor` or `REGEDIT4` header",
+                ))
+            }
+            None => return Err(lexer::error_at(content, 0, "empty registry export")),
+        };
+        let utf16 = version != "REGEDIT4";
+        let mut keys: IndexMap<String, RegKey> = IndexMap::new();
+        let mut current = None;
+        for (start, line) in lines {
+            if let Some(path) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                let (path, deleted) = match path.strip_prefix('-') {
+                    Some(path) => (path, true),
+                    None => (path, false),
+                };
+                let index = match find_key(&keys, path, true) {
+                    Some(index) => index,
+                    None => keys.insert_full(path.to_string(), RegKey::default()).0,
+                };
+                keys[index].deleted |= deleted;
+                current = Some(index);
+                continue;
+            }
+            let Some(index) = current else {
+                return Err(lexer::error_at(
+                    content,
+                    start,
+                    "expected a `[key]` before the first value",
+                ));
+            };
+            let (name, data) = read_name(&line)
+                .ok_or_else(|| lexer::error_at(content, start, "expected `\"name\"=` or `@=`"))?;
+            let value = read_data(data, utf16)
+                .ok_or_else(|| lexer::error_at(content, start, "invalid registry value data"))?;
+            let values = &mut keys[index].values;
+            match find_key(values, &name, true) {
+                Some(existing) => values[existing] = value,
+                None => {
+                    values.insert(name, value);
+                }
+            }
+        }
+        Ok(Self {
+            version: version.to_string(),
+            keys,
+        })
+    }
+
+    /**
+     * Reads and parses a Registry export file. Files written by `regedit`, which are UTF-16
+     * encoded with a byte order mark, are decoded as well as UTF-8 ones.
+     *
+     * # Arguments
+     * * `path` - A string containing the path to the `.reg` file.
+     *
+     * # Returns
+     * Returns an `INIParserResult` containing the `RegFile`, or an `INIParseError` if the file
+     * cannot be read, decoded or parsed.
+     */
+    pub fn from_file(path: &str) -> INIParserResult<Self> {
+        let bytes = fs::read(path).map_err(|err| InIParseError::FileReadError(err.to_string()))?;
+        Self::parse(&decode::decode(bytes)?)
+    }
+
+    /// Returns the header line of the export, e.g. `Windows Registry Editor Version 5.00`.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns a key by its full path, e.g. `HKEY_CURRENT_USER\Software\Demo`.
+    pub fn key(&self, path: &str) -> Option<&RegKey> {
+        find_key(&self.keys, path, true).map(|index| &self.keys[index])
+    }
+
+    /// Returns an iterator over the `(path, key)` pairs of the export, in file order.
+    pub fn keys(&self) -> impl Iterator<Item = (&str, &RegKey)> {
+        self.keys.iter().map(|(path, key)| (path.as_str(), key))
+    }
+}
+
+/// Splits an export into trimmed logical lines with their byte offsets, joining the lines of
+/// `hex:` data continued with a trailing backslash.
+fn logical_lines(content: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+    let mut physical = content
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line.trim()))
+        })
+        .peekable();
+    std::iter::from_fn(move || {
+        let (start, first) = physical.next()?;
+        let mut line = first.to_string();
+        while line.ends_with('\\') {
+            let Some((_, next)) = physical.next() else {
+                break;
+            };
+            line.pop();
+            line.push_str(next);
+        }
+        Some((start, line))
+    })
+}
+
+/// Splits a value line into its name and data. The default value `@` has an empty name.
+fn read_name(line: &str) -> Option<(String, &str)> {
+    if let Some(data) = line.strip_prefix('@') {
+        return Some((String::new(), data.trim_start().strip_prefix('=')?));
+    }
+    let (name, rest) = read_string(line)?;
+    Some((name, rest.trim_start().strip_prefix('=')?))
+}
+
+/// Reads a double-quoted string with `\\` and `\"` escapes, returning it with the rest of
+/// the line.
+fn read_string(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut string = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((string, &text[i + 2..])),
+            '\\' => string.push(chars.next().map_or('\\', |(_, c)| c)),
+            c => string.push(c),
+        }
+    }
+    None
+}
+
+/// Reads the data of a value, decoding `hex(2):` and `hex(7):` strings as UTF-16 unless the
+/// export uses the older `REGEDIT4` format.
+fn read_data(data: &str, utf16: bool) -> Option<RegValue> {
+    let data = data.trim();
+    if data == "-" {
+        return Some(RegValue::Delete);
+    }
+    if data.starts_with('"') {
+        let (string, rest) = read_string(data)?;
+        return rest.trim().is_empty().then_some(RegValue::String(string));
+    }
+    if let Some(number) = data.strip_prefix("dword:") {
+        return u32::from_str_radix(number.trim(), 16)
+            .ok()
+            .map(RegValue::Dword);
+    }
+    let (kind, bytes) = match data.strip_prefix("hex:") {
+        Some(bytes) => (3, bytes),
+        None => {
+            let (kind, bytes) = data.strip_prefix("hex(")?.split_once("):")?;
+            (u32::from_str_radix(kind, 16).ok()?, bytes)
+        }
+    };
+    let bytes = bytes
+        .split(',')
+        .map(str::trim)
+        .filter(|byte| !byte.is_empty())
+        .map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    let text = |bytes: &[u8]| {
+        if utf16 {
+            utf16_le(bytes)
+        } else {
+            bytes.iter().map(|&byte| char::from(byte)).collect()
+        }
+    };
+    Some(match kind {
+        2 => RegValue::ExpandString(text(&bytes).trim_end_matches('\0').to_string()),
+        3 => RegValue::Binary(bytes),
+        7 => {
+            let text = text(&bytes);
+            let text = text.trim_end_matches('\0');
+            RegValue::MultiString(match text {
+                "" => Vec::new(),
+                text => text.split('\0').map(str::to_string).collect(),
+            })
+        }
+        0xb if bytes.len() == 8 => {
+            RegValue::Qword(u64::from_le_bytes(bytes.as_slice().try_into().ok()?))
+        }
+        kind => RegValue::Other { kind, data: bytes },
+    })
+}
+
+/// Decodes UTF-16LE bytes, replacing invalid sequences.
+fn utf16_le(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
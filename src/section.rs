@@ -0,0 +1,412 @@
+use crate::{lexer, Normalization, Properties, Span};
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ops::Index;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// A borrowed, read-only view of a single section of a parsed config.
+/// Obtained through `INIParser::section`, it can be passed around on its own without copying
+/// the rest of the config.
+#[derive(Debug, Clone, Copy)]
+pub struct Section<'a> {
+    name: &'a str,
+    properties: &'a Properties,
+    ignore_case: bool,
+    normalization: Normalization,
+}
+
+impl<'a> Section<'a> {
+    pub(crate) fn new(
+        name: &'a str,
+        properties: &'a Properties,
+        ignore_case: bool,
+        normalization: Normalization,
+    ) -> Self {
+        Self {
+            name,
+            properties,
+            ignore_case,
+            normalization,
+        }
+    }
+
+    /// Returns the name of the section.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /**
+     * Returns the value of a property in this section.
+     *
+     * # Arguments
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns `Some` with the property value, or `None` if the key does not exist.
+     */
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        let key = normalize(key, self.normalization);
+        find_key(self.properties, &key, self.ignore_case)
+            .map(|index| self.properties[index].as_str())
+    }
+
+    /// Returns `true` if the section contains the given key.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the number of properties in the section.
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    /// Returns `true` if the section has no properties.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Returns an iterator over the property names of the section.
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> {
+        self.properties.keys().map(String::as_str)
+    }
+
+    /// Returns an iterator over the `(key, value)` pairs of the section.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.properties
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Returns an iterator over the `(key, value)` pairs whose key starts with `prefix`.
+    pub fn keys_with_prefix<'p>(
+        &self,
+        prefix: &'p str,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> + 'p
+    where
+        'a: 'p,
+    {
+        self.iter().filter(move |(key, _)| key.starts_with(prefix))
+    }
+}
+
+/// Indexes a property of the section, e.g. `section["name"]`.
+///
+/// # Panics
+/// Panics if the key does not exist.
+impl Index<&str> for Section<'_> {
+    type Output = str;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        self.get(key)
+            .unwrap_or_else(|| panic!("key {key:?} not found in section {:?}", self.name))
+    }
+}
+
+/// A mutable view of a single section of a parsed config.
+/// Obtained through `INIParser::section_mut`, it allows targeted in-memory edits of one
+/// section before the config is used or written back.
+#[derive(Debug)]
+pub struct SectionMut<'a> {
+    name: String,
+    properties: &'a mut Properties,
+    /// The values collected for the repeated keys of the section, if any.
+    values: Option<&'a mut HashMap<String, Vec<String>>>,
+    /// Where the key and value of each property were read, if anywhere.
+    spans: Option<&'a mut HashMap<String, (Span, Span)>>,
+    sorted: bool,
+    ignore_case: bool,
+    normalization: Normalization,
+}
+
+/// The parts of a config a `SectionMut` edits besides the properties themselves.
+pub(crate) struct SideTables<'a> {
+    pub(crate) values: Option<&'a mut HashMap<String, Vec<String>>>,
+    pub(crate) spans: Option<&'a mut HashMap<String, (Span, Span)>>,
+}
+
+impl<'a> SectionMut<'a> {
+    pub(crate) fn new(
+        name: String,
+        properties: &'a mut Properties,
+        side_tables: SideTables<'a>,
+        sorted: bool,
+        ignore_case: bool,
+        normalization: Normalization,
+    ) -> Self {
+        Self {
+            name,
+            properties,
+            values: side_tables.values,
+            spans: side_tables.spans,
+            sorted,
+            ignore_case,
+            normalization,
+        }
+    }
+
+    /// Returns the name of the section.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of a property in this section.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let key = normalize(key, self.normalization);
+        find_key(self.properties, &key, self.ignore_case)
+            .map(|index| self.properties[index].as_str())
+    }
+
+    /// Returns the number of properties in the section.
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    /// Returns `true` if the section has no properties.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /**
+     * Sets the value of a property, adding it if it does not exist yet. Like
+     * `INIParser::set`, this replaces the values collected for a repeated key and forgets where
+     * the property was read.
+     *
+     * # Arguments
+     * * `key` - The name of the property.
+     * * `value` - The new value of the property.
+     *
+     * # Returns
+     * Returns the previous value of the property, if there was one.
+     */
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        let key = normalize(&key.into(), self.normalization).into_owned();
+        let key = stored_key(self.properties, &key, self.ignore_case)
+            .cloned()
+            .unwrap_or(key);
+        self.forget(&key);
+        insert_property(self.properties, key, value.into(), self.sorted, false)
+    }
+
+    /**
+     * Removes a property from the section, together with the values collected for it and
+     * where it was read, like `INIParser::remove_key`.
+     *
+     * # Arguments
+     * * `key` - The name of the property to remove.
+     *
+     * # Returns
+     * Returns the removed value, or `None` if the key did not exist.
+     */
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let key = normalize(key, self.normalization);
+        let index = find_key(self.properties, &key, self.ignore_case)?;
+        let (key, value) = self.properties.shift_remove_index(index)?;
+        self.forget(&key);
+        Some(value)
+    }
+
+    /**
+     * Renames a property while keeping its value and position, its collected values and
+     * spans moving along. A property already stored under the new name is overwritten.
+     *
+     * # Arguments
+     * * `from` - The current name of the property.
+     * * `to` - The new name of the property.
+     *
+     * # Returns
+     * Returns `true` if the property existed and was renamed.
+     */
+    pub fn rename_key(&mut self, from: &str, to: impl Into<String>) -> bool {
+        let from = normalize(from, self.normalization);
+        let to = normalize(&to.into(), self.normalization).into_owned();
+        let Some(from) = stored_key(self.properties, &from, self.ignore_case).cloned() else {
+            return false;
+        };
+        if !rename_entry(self.properties, &from, to.clone(), false) {
+            return false;
+        }
+        if from != to {
+            if let Some(values) = self.values.as_deref_mut() {
+                move_entry(values, &from, to.clone());
+            }
+            if let Some(spans) = self.spans.as_deref_mut() {
+                move_entry(spans, &from, to);
+            }
+        }
+        if self.sorted {
+            self.properties.sort_keys();
+        }
+        true
+    }
+
+    /// Drops the collected values and spans of a property that changes.
+    fn forget(&mut self, key: &str) {
+        if let Some(values) = self.values.as_deref_mut() {
+            values.remove(key);
+        }
+        if let Some(spans) = self.spans.as_deref_mut() {
+            spans.remove(key);
+        }
+    }
+}
+
+/// Moves the entry of a side table to a new key, dropping the one it replaces.
+fn move_entry<V>(map: &mut HashMap<String, V>, from: &str, to: String) {
+    map.remove(&to);
+    if let Some(value) = map.remove(from) {
+        map.insert(to, value);
+    }
+}
+
+/// Normalizes a section or key name, borrowing it when it is already normalized.
+pub(crate) fn normalize(name: &str, normalization: Normalization) -> Cow<'_, str> {
+    match normalization {
+        Normalization::None => Cow::Borrowed(name),
+        Normalization::Nfc if is_nfc(name) => Cow::Borrowed(name),
+        Normalization::Nfc => Cow::Owned(name.nfc().collect()),
+        Normalization::NfcCaseFold => {
+            let folded: String = fold_case(&name.nfc().collect::<String>()).collect();
+            match folded == name {
+                true => Cow::Borrowed(name),
+                false => Cow::Owned(folded.nfc().collect()),
+            }
+        }
+    }
+}
+
+/// Builds the name a git-style subsection is stored under, e.g. `remote "origin"`.
+pub(crate) fn subsection_name(section: &str, subsection: &str) -> String {
+    let escaped = subsection.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("{section} \"{escaped}\"")
+}
+
+/// Splits a stored section name into its section and unescaped subsection, if it has one.
+pub(crate) fn split_subsection(name: &str) -> Option<(&str, String)> {
+    let (section, quoted) = name.split_once(' ')?;
+    let quoted_well = quoted.len() >= 2 && quoted.starts_with('"') && quoted.ends_with('"');
+    quoted_well.then(|| (section, lexer::unquote(quoted)))
+}
+
+/// Renames a key of an order-preserving map, moving the entry back to the position the old
+/// key held.
+pub(crate) fn rename_entry<V>(
+    map: &mut IndexMap<String, V>,
+    from: &str,
+    to: String,
+    ignore_case: bool,
+) -> bool {
+    let Some((index, value)) = find_key(map, from, ignore_case).and_then(|index| {
+        map.shift_remove_index(index)
+            .map(|(_, value)| (index, value))
+    }) else {
+        return false;
+    };
+    let (new_index, _) = map.insert_full(to, value);
+    map.move_index(new_index, index.min(map.len() - 1));
+    true
+}
+
+/// Lowercases the characters of a name. Every case-insensitive comparison of names folds them
+/// this way, so that lookups, reading and `Normalization::NfcCaseFold` agree on which names
+/// are the same.
+pub(crate) fn fold_case(name: &str) -> impl Iterator<Item = char> + '_ {
+    name.chars().flat_map(char::to_lowercase)
+}
+
+/// Returns `true` if two names are the same, ignoring case with `ignore_case`.
+pub(crate) fn same_name(a: &str, b: &str, ignore_case: bool) -> bool {
+    a == b || (ignore_case && fold_case(a).eq(fold_case(b)))
+}
+
+/// Finds the position of a key in an order-preserving map. An exact match wins; otherwise,
+/// with `ignore_case`, the first key that only differs in case is used.
+pub(crate) fn find_key<V>(
+    map: &IndexMap<String, V>,
+    key: &str,
+    ignore_case: bool,
+) -> Option<usize> {
+    map.get_index_of(key).or_else(|| {
+        ignore_case
+            .then(|| map.keys().position(|name| same_name(name, key, true)))
+            .flatten()
+    })
+}
+
+/// The names of the sections and keys read so far by their case-folded form, so that
+/// reading with `ignore_case` finds the stored casing of a name without scanning for it.
+/// Names are recorded as they are resolved, the first casing read being the one kept.
+#[derive(Debug, Default)]
+pub(crate) struct FoldedNames {
+    sections: HashMap<String, String>,
+    /// The keys of each section, or of each repeated block of one, by stored section name.
+    keys: HashMap<(String, Option<usize>), HashMap<String, String>>,
+}
+
+impl FoldedNames {
+    /// Returns the name a section is stored under, `name` itself when it is new or
+    /// `ignore_case` is off.
+    pub(crate) fn section(&mut self, name: String, ignore_case: bool) -> String {
+        match ignore_case {
+            true => resolve_folded(&mut self.sections, name),
+            false => name,
+        }
+    }
+
+    /// Returns the name a key of a section, or of a repeated block of it, is stored under,
+    /// `key` itself when it is new or `ignore_case` is off.
+    pub(crate) fn key(
+        &mut self,
+        section: &str,
+        block: Option<usize>,
+        key: String,
+        ignore_case: bool,
+    ) -> String {
+        match ignore_case {
+            true => {
+                let keys = self.keys.entry((section.to_string(), block)).or_default();
+                resolve_folded(keys, key)
+            }
+            false => key,
+        }
+    }
+}
+
+fn resolve_folded(names: &mut HashMap<String, String>, name: String) -> String {
+    match names.entry(fold_case(&name).collect()) {
+        Entry::Occupied(entry) => entry.get().clone(),
+        Entry::Vacant(entry) => entry.insert(name).clone(),
+    }
+}
+
+/// Returns the key a map stores under a name, resolved like `find_key`.
+pub(crate) fn stored_key<'m, V>(
+    map: &'m IndexMap<String, V>,
+    key: &str,
+    ignore_case: bool,
+) -> Option<&'m String> {
+    find_key(map, key, ignore_case)
+        .and_then(|index| map.get_index(index))
+        .map(|(name, _)| name)
+}
+
+/// Inserts a property, at its sorted position when the section is kept sorted.
+/// With `ignore_case`, an existing key that only differs in case is updated in place and
+/// keeps its original casing.
+pub(crate) fn insert_property(
+    properties: &mut Properties,
+    key: String,
+    value: String,
+    sorted: bool,
+    ignore_case: bool,
+) -> Option<String> {
+    if let Some(index) = find_key(properties, &key, ignore_case) {
+        return Some(std::mem::replace(&mut properties[index], value));
+    }
+    if sorted {
+        properties.insert_sorted(key, value).1
+    } else {
+        properties.insert(key, value)
+    }
+}
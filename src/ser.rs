@@ -0,0 +1,648 @@
+use crate::{INIParser, INIParserResult, InIParseError, Properties, Sections, WriteOptions};
+use serde::ser::{self, Impossible, Serialize};
+use std::fmt::Display;
+
+/**
+ * Serializes a type implementing `serde::Serialize` into INI text, the reverse of `from_str`.
+ *
+ * The fields of the top-level struct holding structs or maps are written as sections, and the
+ * other fields as properties of the default section, before the first header. Sequences are
+ * written as comma-separated lists, unit enum variants by their name, and `None` values are
+ * left out. Values that would lose their surrounding whitespace or read back as `None`, like
+ * `Some(String::new())`, are quoted with the default `WriteOptions`.
+ *
+ * # Arguments
+ * * `value` - The config to serialize, a struct or map.
+ *
+ * # Returns
+ * Returns the INI text, or `InIParseError::SerializationError` if the value is not a struct or
+ * map of sections, nests structs deeper than sections, or holds names or values that cannot be
+ * written, such as list items that are empty, padded with whitespace or hold a `,`.
+ *
+ * ```rust
+ * use serde::{Deserialize, Serialize};
+ *
+ * #[derive(Debug, PartialEq, Serialize, Deserialize)]
+ * struct AppConfig {
+ *     name: String,
+ *     server: Server,
+ * }
+ *
+ * #[derive(Debug, PartialEq, Serialize, Deserialize)]
+ * struct Server {
+ *     port: u16,
+ *     tls: bool,
+ *     aliases: Vec<String>,
+ *     timeout: Option<f64>,
+ * }
+ *
+ * let cfg = AppConfig {
+ *     name: "demo".to_string(),
+ *     server: Server { port: 8080, tls: true, aliases: vec!["www".into(), "api".into()], timeout: None },
+ * };
+ * let text = dotini::to_string(&cfg).unwrap();
+ * assert_eq!(text, "name = demo\n\n[server]\nport = 8080\ntls = true\naliases = www, api\n");
+ * assert_eq!(dotini::from_str::<AppConfig>(&text).unwrap(), cfg);
+ * ```
+ *
+ * ```rust
+ * use serde::{Deserialize, Serialize};
+ *
+ * #[derive(Debug, PartialEq, Serialize, Deserialize)]
+ * struct Profile {
+ *     motto: String,
+ *     nickname: Option<String>,
+ *     tags: Vec<String>,
+ * }
+ *
+ * let profile = Profile { motto: "  padded ".into(), nickname: Some(String::new()), tags: vec![] };
+ * let text = dotini::to_string(&profile).unwrap();
+ * assert_eq!(text, "motto = \"  padded \"\nnickname = \"\"\ntags = \"\"\n");
+ * assert_eq!(dotini::from_str::<Profile>(&text).unwrap(), profile);
+ *
+ * let tags = Profile { tags: vec!["a,b".into(), "c".into()], ..profile };
+ * assert!(dotini::to_string(&tags).is_err());
+ * ```
+ */
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> INIParserResult<String> {
+    to_string_with_options(value, &WriteOptions::default())
+}
+
+/**
+ * Serializes a type implementing `serde::Serialize` into INI text using the given options.
+ *
+ * # Arguments
+ * * `value` - The config to serialize, a struct or map.
+ * * `options` - A `WriteOptions` describing the output format.
+ *
+ * # Returns
+ * Returns the INI text, or `InIParseError::SerializationError` as for `to_string`.
+ *
+ * ```rust
+ * use dotini::WriteOptions;
+ * use std::collections::BTreeMap;
+ *
+ * let db = BTreeMap::from([("host", "localhost"), ("port", "5432")]);
+ * let cfg = BTreeMap::from([("db", db)]);
+ * let options = WriteOptions::new().delimiter(':').space_around_delimiter(false);
+ * assert_eq!(dotini::to_string_with_options(&cfg, &options).unwrap(), "[db]\nhost:localhost\nport:5432\n");
+ * ```
+ */
+pub fn to_string_with_options<T: Serialize + ?Sized>(
+    value: &T,
+    options: &WriteOptions,
+) -> INIParserResult<String> {
+    let mut parser = INIParser::from_map(Sections::new());
+    value.serialize(ConfigSerializer(&mut parser))?;
+    parser.to_ini_string_with_options(options)
+}
+
+impl ser::Error for InIParseError {
+    fn custom<T: Display>(message: T) -> Self {
+        Self::SerializationError(message.to_string())
+    }
+}
+
+fn not_a_config() -> InIParseError {
+    InIParseError::SerializationError("a config must be a struct or map of sections".to_string())
+}
+
+fn nested() -> InIParseError {
+    InIParseError::SerializationError(
+        "structs, maps and enum variants with data cannot be written as values".to_string(),
+    )
+}
+
+/// What a field of a config is written as.
+enum Field {
+    /// A property of the default section, or nothing for `None`.
+    Value(Option<String>),
+    Section(Properties),
+}
+
+/// Serializes the top-level struct or map of a config into a parser.
+struct ConfigSerializer<'a>(&'a mut INIParser);
+
+impl<'a> ser::Serializer for ConfigSerializer<'a> {
+    type Ok = ();
+    type Error = InIParseError;
+    type SerializeSeq = Impossible<(), InIParseError>;
+    type SerializeTuple = Impossible<(), InIParseError>;
+    type SerializeTupleStruct = Impossible<(), InIParseError>;
+    type SerializeTupleVariant = Impossible<(), InIParseError>;
+    type SerializeMap = ConfigFields<'a>;
+    type SerializeStruct = ConfigFields<'a>;
+    type SerializeStructVariant = Impossible<(), InIParseError>;
+
+    fn serialize_bool(self, _value: bool) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_i8(self, _value: i8) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_i16(self, _value: i16) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_i32(self, _value: i32) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_i64(self, _value: i64) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_u8(self, _value: u8) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_u16(self, _value: u16) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_u32(self, _value: u32) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_u64(self, _value: u64) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_f32(self, _value: f32) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_f64(self, _value: f64) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_char(self, _value: char) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_str(self, _value: &str) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_none(self) -> INIParserResult<()> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> INIParserResult<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> INIParserResult<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> INIParserResult<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> INIParserResult<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> INIParserResult<()> {
+        Err(not_a_config())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> INIParserResult<Self::SerializeSeq> {
+        Err(not_a_config())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> INIParserResult<Self::SerializeTuple> {
+        Err(not_a_config())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> INIParserResult<Self::SerializeTupleStruct> {
+        Err(not_a_config())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> INIParserResult<Self::SerializeTupleVariant> {
+        Err(not_a_config())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> INIParserResult<Self::SerializeMap> {
+        Ok(ConfigFields {
+            parser: self.0,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> INIParserResult<Self::SerializeStruct> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> INIParserResult<Self::SerializeStructVariant> {
+        Err(not_a_config())
+    }
+}
+
+/// Adds the fields of a config to a parser as they are serialized.
+struct ConfigFields<'a> {
+    parser: &'a mut INIParser,
+    /// The key of the map entry whose value comes next.
+    key: Option<String>,
+}
+
+impl ConfigFields<'_> {
+    fn insert(&mut self, key: String, field: Field) {
+        match field {
+            Field::Value(None) => (),
+            Field::Value(Some(value)) => {
+                let section = self.parser.options.default_section.clone();
+                self.parser.set(&section, key, value);
+            }
+            Field::Section(properties) => {
+                self.parser.entry(&key).or_default();
+                for (name, value) in properties {
+                    self.parser.set(&key, name, value);
+                }
+            }
+        }
+    }
+}
+
+impl ser::SerializeMap for ConfigFields<'_> {
+    type Ok = ();
+    type Error = InIParseError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> INIParserResult<()> {
+        self.key = Some(key_name(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> INIParserResult<()> {
+        let key = self.key.take().ok_or(InIParseError::Unreachable)?;
+        let field = value.serialize(FieldSerializer { nested: false })?;
+        self.insert(key, field);
+        Ok(())
+    }
+
+    fn end(self) -> INIParserResult<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for ConfigFields<'_> {
+    type Ok = ();
+    type Error = InIParseError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> INIParserResult<()> {
+        let field = value.serialize(FieldSerializer { nested: false })?;
+        self.insert(key.to_string(), field);
+        Ok(())
+    }
+
+    fn end(self) -> INIParserResult<()> {
+        Ok(())
+    }
+}
+
+/// Returns the name a map key is written under.
+fn key_name<T: Serialize + ?Sized>(key: &T) -> INIParserResult<String> {
+    match key.serialize(FieldSerializer { nested: true })? {
+        Field::Value(Some(key)) => Ok(key),
+        _ => Err(InIParseError::SerializationError(
+            "map keys must be strings, numbers or unit variants".to_string(),
+        )),
+    }
+}
+
+/// Serializes a field of a config, or with `nested`, a property of a section or an item of a
+/// list, which cannot be a section.
+struct FieldSerializer {
+    nested: bool,
+}
+
+impl FieldSerializer {
+    fn value(value: impl ToString) -> INIParserResult<Field> {
+        Ok(Field::Value(Some(value.to_string())))
+    }
+}
+
+impl ser::Serializer for FieldSerializer {
+    type Ok = Field;
+    type Error = InIParseError;
+    type SerializeSeq = List;
+    type SerializeTuple = List;
+    type SerializeTupleStruct = List;
+    type SerializeTupleVariant = Impossible<Field, InIParseError>;
+    type SerializeMap = SectionFields;
+    type SerializeStruct = SectionFields;
+    type SerializeStructVariant = Impossible<Field, InIParseError>;
+
+    fn serialize_bool(self, value: bool) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_i8(self, value: i8) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_i16(self, value: i16) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_i32(self, value: i32) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_i64(self, value: i64) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_u8(self, value: u8) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_u16(self, value: u16) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_u32(self, value: u32) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_u64(self, value: u64) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_f32(self, value: f32) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_f64(self, value: f64) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_char(self, value: char) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_str(self, value: &str) -> INIParserResult<Field> {
+        Self::value(value)
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> INIParserResult<Field> {
+        let value = std::str::from_utf8(value).map_err(|err| {
+            InIParseError::SerializationError(format!("bytes cannot be written as text: {err}"))
+        })?;
+        Self::value(value)
+    }
+
+    fn serialize_none(self) -> INIParserResult<Field> {
+        Ok(Field::Value(None))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> INIParserResult<Field> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> INIParserResult<Field> {
+        Self::value("")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> INIParserResult<Field> {
+        Self::value("")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> INIParserResult<Field> {
+        Self::value(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> INIParserResult<Field> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> INIParserResult<Field> {
+        Err(nested())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> INIParserResult<List> {
+        Ok(List { items: Vec::new() })
+    }
+
+    fn serialize_tuple(self, len: usize) -> INIParserResult<List> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> INIParserResult<List> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> INIParserResult<Self::SerializeTupleVariant> {
+        Err(nested())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> INIParserResult<SectionFields> {
+        if self.nested {
+            return Err(nested());
+        }
+        Ok(SectionFields {
+            properties: Properties::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> INIParserResult<SectionFields> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> INIParserResult<Self::SerializeStructVariant> {
+        Err(nested())
+    }
+}
+
+/// Collects the items of a comma-separated value.
+struct List {
+    items: Vec<String>,
+}
+
+impl List {
+    /// Adds an item, which must read back as a single unchanged item of the list.
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> INIParserResult<()> {
+        if let Field::Value(Some(item)) = value.serialize(FieldSerializer { nested: true })? {
+            if item.is_empty() || item.trim() != item || item.contains(',') {
+                return Err(InIParseError::SerializationError(format!(
+                    "list item {item:?} cannot be written in a comma-separated value"
+                )));
+            }
+            self.items.push(item);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> INIParserResult<Field> {
+        Ok(Field::Value(Some(self.items.join(", "))))
+    }
+}
+
+impl ser::SerializeSeq for List {
+    type Ok = Field;
+    type Error = InIParseError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> INIParserResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> INIParserResult<Field> {
+        List::end(self)
+    }
+}
+
+impl ser::SerializeTuple for List {
+    type Ok = Field;
+    type Error = InIParseError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> INIParserResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> INIParserResult<Field> {
+        List::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for List {
+    type Ok = Field;
+    type Error = InIParseError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> INIParserResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> INIParserResult<Field> {
+        List::end(self)
+    }
+}
+
+/// Collects the properties of a section.
+struct SectionFields {
+    properties: Properties,
+    /// The key of the map entry whose value comes next.
+    key: Option<String>,
+}
+
+impl SectionFields {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> INIParserResult<()> {
+        if let Field::Value(Some(value)) = value.serialize(FieldSerializer { nested: true })? {
+            self.properties.insert(key, value);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SectionFields {
+    type Ok = Field;
+    type Error = InIParseError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> INIParserResult<()> {
+        self.key = Some(key_name(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> INIParserResult<()> {
+        let key = self.key.take().ok_or(InIParseError::Unreachable)?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> INIParserResult<Field> {
+        Ok(Field::Section(self.properties))
+    }
+}
+
+impl ser::SerializeStruct for SectionFields {
+    type Ok = Field;
+    type Error = InIParseError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> INIParserResult<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> INIParserResult<Field> {
+        Ok(Field::Section(self.properties))
+    }
+}
@@ -0,0 +1,210 @@
+use crate::INIParser;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Where a section name, key or value was read, for editors, linters and messages like
+/// "defined at config.ini:42".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The line it starts on, starting at 1.
+    pub line: usize,
+    /// Its byte range in the text it was read from, as written.
+    pub range: Range<usize>,
+    /// The file it was read from, or `None` for text parsed from a string.
+    pub file: Option<PathBuf>,
+}
+
+/// Formats as `file:line`, or `line N` for text parsed from a string.
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}", file.display(), self.line),
+            None => write!(f, "line {}", self.line),
+        }
+    }
+}
+
+/// The spans recorded while parsing, by stored section and key names.
+#[derive(Debug, Default)]
+pub(crate) struct Spans {
+    sections: HashMap<String, Span>,
+    /// The spans of the key and value of each property.
+    properties: HashMap<String, HashMap<String, (Span, Span)>>,
+}
+
+impl Spans {
+    pub(crate) fn record_section(&mut self, section: &str, span: Span) {
+        self.sections.entry(section.to_string()).or_insert(span);
+    }
+
+    pub(crate) fn record_property(&mut self, section: &str, key: String, spans: (Span, Span)) {
+        self.properties
+            .entry(section.to_string())
+            .or_default()
+            .insert(key, spans);
+    }
+
+    /// Forgets the spans of a property whose value no longer comes from the input.
+    pub(crate) fn forget_property(&mut self, section: &str, key: &str) {
+        if let Some(properties) = self.properties.get_mut(section) {
+            properties.remove(key);
+        }
+    }
+
+    /// Returns the spans of the properties of a section, by stored key.
+    pub(crate) fn properties_mut(
+        &mut self,
+        section: &str,
+    ) -> Option<&mut HashMap<String, (Span, Span)>> {
+        self.properties.get_mut(section)
+    }
+
+    /// Forgets the spans of a removed section and of its properties.
+    pub(crate) fn forget_section(&mut self, section: &str) {
+        self.sections.remove(section);
+        self.properties.remove(section);
+    }
+
+    /// Moves the spans of a section and of its properties to its new name.
+    pub(crate) fn rename_section(&mut self, from: &str, to: String) {
+        if let Some(span) = self.sections.remove(from) {
+            self.sections.insert(to.clone(), span);
+        }
+        if let Some(properties) = self.properties.remove(from) {
+            self.properties.insert(to, properties);
+        }
+    }
+
+    pub(crate) fn take_section(&mut self, section: &str) -> Option<Span> {
+        self.sections.remove(section)
+    }
+
+    pub(crate) fn take_property(&mut self, section: &str, key: &str) -> Option<(Span, Span)> {
+        self.properties.get_mut(section)?.remove(key)
+    }
+}
+
+impl INIParser {
+    /**
+     * Returns where the header of a section was read. A section repeated in the input points
+     * at its first header.
+     *
+     * # Arguments
+     * * `section` - The name of the section.
+     *
+     * # Returns
+     * Returns `None` if the section does not exist or was not read from a header, like the
+     * default section or sections added after parsing.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "port = 80\n\n[server]\nhost = localhost\n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * let span = parser.section_span("server").unwrap();
+     * assert_eq!((span.line, &content[span.range.clone()]), (3, "server"));
+     * assert_eq!(span.to_string(), "line 3");
+     * assert_eq!(parser.section_span("untagged"), None);
+     * ```
+     */
+    pub fn section_span(&self, section: &str) -> Option<&Span> {
+        let (name, _) = self.lookup(section)?;
+        self.spans.sections.get(name)
+    }
+
+    /**
+     * Returns where the key of a property was read. A repeated key points at the occurrence
+     * whose value was kept.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns `None` if the property does not exist or was not read from the input.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let dir = tempfile::tempdir().unwrap();
+     * let path = dir.path().join("server.ini");
+     * std::fs::write(&path, "[server]\nport = 80\nport = 8080\n").unwrap();
+     * let parser = INIParser::from_file(path.to_str().unwrap()).unwrap();
+     * let span = parser.key_span("server", "port").unwrap();
+     * assert_eq!(span.line, 3);
+     * assert_eq!(span.range, 19..23);
+     * assert_eq!(span.to_string(), format!("{}:3", path.display()));
+     * ```
+     */
+    pub fn key_span(&self, section: &str, key: &str) -> Option<&Span> {
+        self.property_spans(section, key).map(|(key, _)| key)
+    }
+
+    /**
+     * Returns where the value of a property was read, as written, quotes included. The range
+     * of a value continued over several lines spans all of them.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns `None` if the property does not exist or was not read from the input.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let content = "[server]\nhost = \"localhost\" \n";
+     * let parser = INIParser::from_string(content).unwrap();
+     * let span = parser.value_span("server", "host").unwrap();
+     * assert_eq!(&content[span.range.clone()], "\"localhost\"");
+     * ```
+     */
+    pub fn value_span(&self, section: &str, key: &str) -> Option<&Span> {
+        self.property_spans(section, key).map(|(_, value)| value)
+    }
+
+    /**
+     * Returns where the effective value of a property came from, which for layered configs
+     * built with `merge` or `include` directives is the file and line of the layer that set
+     * it last.
+     *
+     * # Arguments
+     * * `section` - The name of the section the property belongs to.
+     * * `key` - The name of the property.
+     *
+     * # Returns
+     * Returns the span of the value, or `None` if the property does not exist or its value was
+     * set after parsing.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let dir = tempfile::tempdir().unwrap();
+     * let (base, local) = (dir.path().join("base.ini"), dir.path().join("local.ini"));
+     * std::fs::write(&base, "[db]\nhost = localhost\nport = 5432\n").unwrap();
+     * std::fs::write(&local, "[db]\nport = 6432\n").unwrap();
+     *
+     * let mut parser = INIParser::from_file(base.to_str().unwrap()).unwrap();
+     * parser.merge(INIParser::from_file(local.to_str().unwrap()).unwrap());
+     * assert_eq!(parser.get("db", "port"), Some("6432"));
+     * assert_eq!(parser.origin("db", "port").unwrap().to_string(), format!("{}:2", local.display()));
+     * assert_eq!(parser.origin("db", "host").unwrap().to_string(), format!("{}:2", base.display()));
+     *
+     * parser.set("db", "port", "7432");
+     * assert_eq!(parser.origin("db", "port"), None);
+     * ```
+     */
+    pub fn origin(&self, section: &str, key: &str) -> Option<&Span> {
+        self.value_span(section, key)
+    }
+
+    fn property_spans(&self, section: &str, key: &str) -> Option<&(Span, Span)> {
+        let (name, properties) = self.lookup(section)?;
+        let (_, key) = self.stored_names(section, key);
+        properties.get(&key)?;
+        self.spans.properties.get(name)?.get(&key)
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{Properties, Sections};
+use indexmap::IndexMap;
+
+/// A node of the hierarchy formed by dotted section names such as `[database.replica.eu]`.
+/// Created by `INIParser::tree`, the root holds the properties of the default section and every
+/// other section is reached by following the dot-separated parts of its name. Nodes are also
+/// created for the intermediate parts, whether or not a section of that name exists.
+#[derive(Debug, Clone, Default)]
+pub struct SectionTree<'a> {
+    path: &'a str,
+    properties: Option<&'a Properties>,
+    children: IndexMap<&'a str, SectionTree<'a>>,
+}
+
+impl<'a> SectionTree<'a> {
+    pub(crate) fn new(output: &'a Sections, default_section: &str) -> Self {
+        let mut root = Self::default();
+        for (name, properties) in output {
+            if name == default_section {
+                root.properties = Some(properties);
+                continue;
+            }
+            let mut node = &mut root;
+            let mut end = 0;
+            for part in name.split('.') {
+                end += part.len();
+                let path = &name[..end];
+                end += 1;
+                node = node.children.entry(part).or_insert_with(|| Self {
+                    path,
+                    ..Self::default()
+                });
+            }
+            node.properties = Some(properties);
+        }
+        root
+    }
+
+    /// Returns the full dotted name of the node, which is empty for the root.
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Returns the properties of the section with this exact name, or `None` if the node only
+    /// groups other sections.
+    pub fn properties(&self) -> Option<&'a Properties> {
+        self.properties
+    }
+
+    /// Returns the value of a property of the section with this exact name.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.properties?.get(key).map(String::as_str)
+    }
+
+    /// Returns the direct child with the given name part.
+    pub fn child(&self, name: &str) -> Option<&SectionTree<'a>> {
+        self.children.get(name)
+    }
+
+    /// Returns an iterator over the direct children and their name parts, in the order their
+    /// sections were first seen.
+    pub fn children(&self) -> impl Iterator<Item = (&'a str, &SectionTree<'a>)> {
+        self.children.iter().map(|(name, child)| (*name, child))
+    }
+
+    /**
+     * Returns the descendant reached by following a dotted path.
+     *
+     * # Arguments
+     * * `path` - The dot-separated parts to follow, relative to this node.
+     *
+     * # Returns
+     * Returns `Some` with the node, or `None` if there is no section below that path.
+     */
+    pub fn find(&self, path: &str) -> Option<&SectionTree<'a>> {
+        path.split('.')
+            .try_fold(self, |node, part| node.children.get(part))
+    }
+}
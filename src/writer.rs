@@ -0,0 +1,1174 @@
+use crate::{section, INIParser, INIParserResult, InIParseError, Properties, DEFAULT_SECTION};
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::fmt::{self, Display, Write};
+use std::fs::{self, File};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// When values are wrapped in quotes by the writer. Quoted values are written in double quotes,
+/// or in single quotes if they hold `"` or `\`, since escapes are only written with
+/// `WriteOptions::escapes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotePolicy {
+    /// Values are always written verbatim.
+    Never,
+    /// Values are quoted when they are empty, have leading or trailing whitespace, or contain
+    /// characters that would otherwise be read as comments or quotes.
+    WhenNeeded,
+    /// Every value is quoted.
+    Always,
+}
+
+/// The line terminator written at the end of each line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`, as used on Unix.
+    #[default]
+    Lf,
+    /// `\r\n`, as used on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the terminator as a string.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Returns the line ending matching a terminator found in the input, if any.
+    pub(crate) fn from_eol(eol: &str) -> Option<Self> {
+        match eol {
+            "\n" => Some(LineEnding::Lf),
+            "\r\n" => Some(LineEnding::CrLf),
+            _ => None,
+        }
+    }
+}
+
+/// The character encoding of files and streams written by the writer.
+/// Serializing to a `String` always produces Unicode text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// UTF-8 without a byte order mark.
+    #[default]
+    Utf8,
+    /// UTF-16 little-endian, starting with a byte order mark, as expected by many Windows
+    /// applications.
+    Utf16Le,
+    /// ISO-8859-1. Characters above U+00FF cannot be written.
+    Latin1,
+}
+
+impl Encoding {
+    /// Returns the byte order mark written at the start of the output.
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf16Le => &[0xFF, 0xFE],
+            Encoding::Utf8 | Encoding::Latin1 => &[],
+        }
+    }
+
+    /// Appends the encoded bytes of `text` to `out`.
+    fn encode_into(self, text: &str, out: &mut Vec<u8>) -> INIParserResult<()> {
+        match self {
+            Encoding::Utf8 => out.extend_from_slice(text.as_bytes()),
+            Encoding::Utf16Le => out.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+            Encoding::Latin1 => {
+                for c in text.chars() {
+                    let byte = u8::try_from(c).map_err(|_| {
+                        InIParseError::SerializationError(format!(
+                            "character {c:?} cannot be encoded as Latin-1"
+                        ))
+                    })?;
+                    out.push(byte);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes a whole file, including its byte order mark.
+    fn encode(self, text: &str) -> INIParserResult<Vec<u8>> {
+        let mut out = self.bom().to_vec();
+        self.encode_into(text, &mut out)?;
+        Ok(out)
+    }
+}
+
+/// Options controlling how a config is serialized into INI text.
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    pub(crate) delimiter: char,
+    pub(crate) space_around_delimiter: bool,
+    pub(crate) quote: QuotePolicy,
+    pub(crate) blank_line_between_sections: bool,
+    pub(crate) align_delimiters: bool,
+    pub(crate) atomic: bool,
+    pub(crate) backups: usize,
+    pub(crate) lock: bool,
+    pub(crate) line_ending: Option<LineEnding>,
+    pub(crate) encoding: Encoding,
+    pub(crate) only_if_changed: bool,
+    pub(crate) escapes: bool,
+    pub(crate) escape_unicode: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: '=',
+            space_around_delimiter: true,
+            quote: QuotePolicy::WhenNeeded,
+            blank_line_between_sections: true,
+            align_delimiters: false,
+            atomic: false,
+            backups: 0,
+            lock: false,
+            line_ending: None,
+            encoding: Encoding::Utf8,
+            only_if_changed: false,
+            escapes: false,
+            escape_unicode: false,
+        }
+    }
+}
+
+impl WriteOptions {
+    /// Creates the default options, producing `key = value` lines and a blank line between
+    /// sections, and quoting the values that would not be read back unchanged otherwise.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the character written between keys and values, usually `=` or `:`.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Writes a space on both sides of the delimiter.
+    pub fn space_around_delimiter(mut self, space: bool) -> Self {
+        self.space_around_delimiter = space;
+        self
+    }
+
+    /**
+     * Sets when values are wrapped in quotes.
+     *
+     * ```rust
+     * use dotini::{INIParser, QuotePolicy, WriteOptions};
+     *
+     * let mut parser = INIParser::from_map(Default::default());
+     * parser.set("paths", "tools", "C:\\Tools");
+     * parser.set("paths", "greeting", " \"hi\" ");
+     * let options = WriteOptions::new().quote(QuotePolicy::WhenNeeded);
+     * let text = parser.to_ini_string_with_options(&options).unwrap();
+     * assert_eq!(text, "[paths]\ntools = C:\\Tools\ngreeting = ' \"hi\" '\n");
+     * assert_eq!(INIParser::from_string(&text).unwrap(), parser);
+     *
+     * parser.set("paths", "mixed", "it's \"C:\\\"");
+     * assert!(parser.to_ini_string_with_options(&options).is_err());
+     * ```
+     */
+    pub fn quote(mut self, quote: QuotePolicy) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Writes an empty line before each section header.
+    pub fn blank_line_between_sections(mut self, blank_line: bool) -> Self {
+        self.blank_line_between_sections = blank_line;
+        self
+    }
+
+    /**
+     * Pads keys so that the delimiters of all properties in a section line up in one column.
+     *
+     * ```rust
+     * use dotini::{INIParser, WriteOptions};
+     *
+     * let mut parser = INIParser::from_string("[db]\nhost=localhost\n").unwrap();
+     * parser.set("db", "max_connections", "10");
+     * let text = parser.to_ini_string_with_options(&WriteOptions::new().align_delimiters(true)).unwrap();
+     * assert!(text.contains("host            = localhost\n"));
+     * assert!(text.contains("max_connections = 10\n"));
+     * ```
+     */
+    pub fn align_delimiters(mut self, align: bool) -> Self {
+        self.align_delimiters = align;
+        self
+    }
+
+    /// Saves files by writing a temporary file next to the target, syncing it to disk and
+    /// renaming it over the target, so an interrupted save never leaves a truncated file.
+    pub fn atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+
+    /// Keeps up to `count` copies of previous versions of a file when it is overwritten.
+    /// The most recent copy is `<file>.bak.1` and older ones are shifted up to
+    /// `<file>.bak.<count>`, each keeping the modification time of the version it holds.
+    pub fn backups(mut self, count: usize) -> Self {
+        self.backups = count;
+        self
+    }
+
+    /// Holds an exclusive advisory lock on the file while it is written, so processes using
+    /// `INIParser::from_file_locked` never observe a partial write. The file is rewritten in
+    /// place, so this takes precedence over `atomic`.
+    pub fn lock(mut self, lock: bool) -> Self {
+        self.lock = lock;
+        self
+    }
+
+    /**
+     * Sets the line terminator to write, instead of the one detected in the parsed input.
+     * Configs that were not parsed from text, and `IniWriter`, default to `LineEnding::Lf`.
+     *
+     * ```rust
+     * use dotini::{INIParser, LineEnding, WriteOptions};
+     *
+     * let parser = INIParser::from_string("[server]\r\nport=8080\r\n").unwrap();
+     * assert_eq!(parser.to_ini_string().unwrap(), "[server]\r\nport = 8080\r\n");
+     * let options = WriteOptions::new().line_ending(LineEnding::Lf);
+     * assert_eq!(parser.to_ini_string_with_options(&options).unwrap(), "[server]\nport = 8080\n");
+     * ```
+     */
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = Some(line_ending);
+        self
+    }
+
+    /**
+     * Sets the character encoding of files and streams, UTF-8 by default.
+     *
+     * ```rust
+     * use dotini::{Encoding, INIParser, WriteOptions};
+     *
+     * let mut parser = INIParser::from_map(Default::default());
+     * parser.set("untagged", "name", "caf\u{e9}");
+     * let mut out = Vec::new();
+     * parser.write_to_with_options(&mut out, &WriteOptions::new().encoding(Encoding::Latin1)).unwrap();
+     * assert_eq!(out, b"name = caf\xe9\n");
+     *
+     * let mut out = Vec::new();
+     * parser.write_to_with_options(&mut out, &WriteOptions::new().encoding(Encoding::Utf16Le)).unwrap();
+     * assert_eq!(out[..4], [0xFF, 0xFE, b'n', 0]);
+     * ```
+     */
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /**
+     * Leaves a file untouched when it already holds exactly the bytes that would be written,
+     * so its modification time is kept and no backup is made.
+     *
+     * ```rust
+     * use dotini::{INIParser, WriteOptions};
+     *
+     * let dir = tempfile::tempdir().unwrap();
+     * let path = dir.path().join("app.ini");
+     * let path = path.to_str().unwrap();
+     * let parser = INIParser::from_string("[server]\nport=8080\n").unwrap();
+     * parser.write_to_file(path).unwrap();
+     * let modified = std::fs::metadata(path).unwrap().modified().unwrap();
+     * parser.write_to_file_with_options(path, &WriteOptions::new().only_if_changed(true)).unwrap();
+     * assert_eq!(std::fs::metadata(path).unwrap().modified().unwrap(), modified);
+     * ```
+     */
+    pub fn only_if_changed(mut self, only_if_changed: bool) -> Self {
+        self.only_if_changed = only_if_changed;
+        self
+    }
+
+    /**
+     * Writes line breaks and tabs in values as `\n`, `\r` and `\t` inside double quotes, so
+     * multi-line values can be saved and read back with `ParserOptions::escapes` enabled.
+     * Without it, values spanning several lines cannot be written.
+     *
+     * ```rust
+     * use dotini::{INIParser, ParserOptions, WriteOptions};
+     *
+     * let mut parser = INIParser::from_map(Default::default());
+     * parser.set("motd", "text", "Hello,\n\tworld");
+     * let text = parser.to_ini_string_with_options(&WriteOptions::new().escapes(true)).unwrap();
+     * assert_eq!(text, "[motd]\ntext = \"Hello,\\n\\tworld\"\n");
+     *
+     * let options = ParserOptions::new().escapes(true);
+     * assert_eq!(INIParser::from_string_with_options(&text, options).unwrap(), parser);
+     * ```
+     */
+    pub fn escapes(mut self, escapes: bool) -> Self {
+        self.escapes = escapes;
+        self
+    }
+
+    /**
+     * Writes non-ASCII characters of values as `\uXXXX` escapes, or `\x{...}` beyond the Basic
+     * Multilingual Plane, inside double quotes, so the output is plain ASCII. The values read
+     * back unchanged with `ParserOptions::escapes` enabled.
+     *
+     * ```rust
+     * use dotini::{INIParser, WriteOptions};
+     *
+     * let mut parser = INIParser::from_map(Default::default());
+     * parser.set("menu", "item", "Caf\u{e9} \u{1F375}");
+     * let text = parser.to_ini_string_with_options(&WriteOptions::new().escape_unicode(true)).unwrap();
+     * assert_eq!(text, "[menu]\nitem = \"Caf\\u00e9 \\x{1f375}\"\n");
+     * ```
+     */
+    pub fn escape_unicode(mut self, escape_unicode: bool) -> Self {
+        self.escape_unicode = escape_unicode;
+        self
+    }
+
+    /// Returns the line terminator to write.
+    fn eol(&self) -> &'static str {
+        self.line_ending.unwrap_or_default().as_str()
+    }
+}
+
+impl INIParser {
+    /// Returns the line ending of the parsed input, taken from its first line break.
+    /// It is used when writing the config unless `WriteOptions::line_ending` overrides it.
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Fills in the line ending of the options with the detected one unless it is set.
+    fn resolve(&self, options: &WriteOptions) -> WriteOptions {
+        WriteOptions {
+            line_ending: Some(options.line_ending.unwrap_or(self.line_ending)),
+            ..options.clone()
+        }
+    }
+
+    /**
+     * Serializes the config back into INI text.
+     *
+     * Properties of the default section, see `ParserOptions::default_section`, are written
+     * first without a section header, followed by every other section with its properties.
+     *
+     * # Returns
+     * Returns a `String` containing the INI representation of the config, which can be parsed
+     * again with `INIParser::from_string`, or `InIParseError::SerializationError` if the config
+     * holds names or values that cannot be represented as INI text, such as a value spanning
+     * several lines or a section name with characters the grammar does not allow, like `#`.
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("[server]\nport=8080\n").unwrap();
+     * let text = parser.to_ini_string().unwrap();
+     * assert_eq!(text, "[server]\nport = 8080\n");
+     * assert_eq!(INIParser::from_string(&text).unwrap(), parser);
+     * ```
+     *
+     * Values that would lose their surrounding whitespace or quotes are written in quotes:
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let parser = INIParser::from_string("name = \"John  Doe \"\nnick = \"'quoted'\"\n").unwrap();
+     * let text = parser.to_ini_string().unwrap();
+     * assert_eq!(text, "name = \"John  Doe \"\nnick = \"'quoted'\"\n");
+     * assert_eq!(INIParser::from_string(&text).unwrap(), parser);
+     * ```
+     *
+     * Keys with characters that a bare key cannot hold, such as spaces, delimiters or
+     * brackets, are written in double quotes:
+     *
+     * ```rust
+     * use dotini::INIParser;
+     *
+     * let mut parser = INIParser::from_map(Default::default());
+     * parser.set("paths", "my key = [x]", "1");
+     * let text = parser.to_ini_string().unwrap();
+     * assert_eq!(text, "[paths]\n\"my key = [x]\" = 1\n");
+     * assert_eq!(INIParser::from_string(&text).unwrap().get("paths", "my key = [x]"), Some("1"));
+     *
+     * parser.set("paths", "x", "1\n[admin]\npw = hunter2");
+     * assert!(parser.to_ini_string().is_err());
+     * parser.remove_key("paths", "x");
+     * parser.set("a:b", "x", "1");
+     * assert!(parser.to_ini_string().is_err());
+     * ```
+     */
+    pub fn to_ini_string(&self) -> INIParserResult<String> {
+        self.to_ini_string_with_options(&WriteOptions::default())
+    }
+
+    /**
+     * Serializes the config back into INI text using the given options.
+     *
+     * # Arguments
+     * * `options` - A `WriteOptions` describing the output format.
+     *
+     * # Returns
+     * Returns a `String` containing the INI representation of the config, or
+     * `InIParseError::SerializationError` as for `to_ini_string`.
+     *
+     * ```rust
+     * use dotini::{INIParser, QuotePolicy, WriteOptions};
+     *
+     * let parser = INIParser::from_string("[server]\nport=8080\n").unwrap();
+     * let options = WriteOptions::new()
+     *     .delimiter(':')
+     *     .space_around_delimiter(false)
+     *     .quote(QuotePolicy::Always);
+     * assert_eq!(parser.to_ini_string_with_options(&options).unwrap(), "[server]\nport:\"8080\"\n");
+     * ```
+     */
+    pub fn to_ini_string_with_options(&self, options: &WriteOptions) -> INIParserResult<String> {
+        validate(self, options)?;
+        let mut text = String::new();
+        write_ini(&mut text, self, &self.resolve(options))
+            .expect("writing to a String cannot fail");
+        Ok(text)
+    }
+
+    /**
+     * Serializes the config and writes it to a file, replacing any existing content.
+     *
+     * # Arguments
+     * * `path` - A string containing the path of the file to write.
+     *
+     * # Returns
+     * Returns `InIParseError::SerializationError` if the config holds names or values that
+     * cannot be represented as INI text, or `InIParseError::FileWriteError` if the file cannot
+     * be written.
+     */
+    pub fn write_to_file(&self, path: &str) -> INIParserResult<()> {
+        self.write_to_file_with_options(path, &WriteOptions::default())
+    }
+
+    /**
+     * Serializes the config into any `io::Write`, such as a socket or standard output.
+     *
+     * # Arguments
+     * * `out` - The writer receiving the INI text.
+     *
+     * # Returns
+     * Returns `InIParseError::SerializationError` if the config cannot be represented as INI
+     * text, or `InIParseError::FileWriteError` if writing fails.
+     */
+    pub fn write_to<W: io::Write>(&self, out: &mut W) -> INIParserResult<()> {
+        self.write_to_with_options(out, &WriteOptions::default())
+    }
+
+    /**
+     * Serializes the config into any `io::Write` using the given options.
+     *
+     * # Arguments
+     * * `out` - The writer receiving the INI text.
+     * * `options` - A `WriteOptions` describing the output format.
+     *
+     * # Returns
+     * Returns `InIParseError::SerializationError` if the config cannot be represented as INI
+     * text, or `InIParseError::FileWriteError` if writing fails.
+     */
+    pub fn write_to_with_options<W: io::Write>(
+        &self,
+        out: &mut W,
+        options: &WriteOptions,
+    ) -> INIParserResult<()> {
+        validate(self, options)?;
+        let mut adapter = IoAdapter::new(out, options.encoding);
+        adapter.write_bom()?;
+        let result = write_ini(&mut adapter, self, &self.resolve(options));
+        adapter.finish(result)
+    }
+
+    /**
+     * Serializes the config and writes it to a file while holding an exclusive advisory lock
+     * on it, the same as `write_to_file` with `WriteOptions::lock` enabled.
+     *
+     * # Arguments
+     * * `path` - A string containing the path of the file to write.
+     *
+     * # Returns
+     * Returns `InIParseError::SerializationError` if the config cannot be represented as INI
+     * text, or `InIParseError::FileWriteError` if the file cannot be locked or written.
+     */
+    pub fn write_to_file_locked(&self, path: &str) -> INIParserResult<()> {
+        self.write_to_file_with_options(path, &WriteOptions::default().lock(true))
+    }
+
+    /**
+     * Serializes the config using the given options and writes it to a file, replacing any
+     * existing content.
+     *
+     * # Arguments
+     * * `path` - A string containing the path of the file to write.
+     * * `options` - A `WriteOptions` describing the output format.
+     *
+     * # Returns
+     * Returns `InIParseError::SerializationError` if the config holds names or values that
+     * cannot be represented as INI text, or `InIParseError::FileWriteError` if the file cannot
+     * be written.
+     *
+     * ```rust
+     * use dotini::{INIParser, WriteOptions};
+     *
+     * let dir = tempfile::tempdir().unwrap();
+     * let path = dir.path().join("app.ini");
+     * let path = path.to_str().unwrap();
+     * let parser = INIParser::from_string("[server]\nport=8080\n").unwrap();
+     * parser.write_to_file_with_options(path, &WriteOptions::new().atomic(true)).unwrap();
+     * assert_eq!(INIParser::from_file(path).unwrap(), parser);
+     * ```
+     */
+    pub fn write_to_file_with_options(
+        &self,
+        path: &str,
+        options: &WriteOptions,
+    ) -> INIParserResult<()> {
+        let content = options
+            .encoding
+            .encode(&self.to_ini_string_with_options(options)?)?;
+        let path = Path::new(path);
+        if options.only_if_changed && fs::read(path).is_ok_and(|existing| existing == content) {
+            return Ok(());
+        }
+        rotate_backups(path, options.backups)
+            .and_then(|_| {
+                if options.lock {
+                    write_locked(path, &content)
+                } else if options.atomic {
+                    write_atomic(path, &content)
+                } else {
+                    fs::write(path, content)
+                }
+            })
+            .map_err(|err| InIParseError::FileWriteError(err.to_string()))
+    }
+}
+
+/// Serializes the config back into INI text, the same as `INIParser::to_ini_string`.
+/// Formatting never fails: a config that `to_ini_string` rejects is still shown, its values
+/// written with escapes and the line breaks of its names escaped, so no line of the output
+/// comes from inside a name or value.
+///
+/// ```rust
+/// use dotini::INIParser;
+///
+/// let mut parser = INIParser::from_string("[server]\nhost=localhost\n").unwrap();
+/// assert_eq!(parser.to_string(), "[server]\nhost = localhost\n");
+/// assert_eq!(format!("{parser}"), parser.to_ini_string().unwrap());
+///
+/// parser.set("server", "motd", "hello\n[admin]\npw = hunter2");
+/// parser.set("bad]name", "k", "v");
+/// assert!(parser.to_ini_string().is_err());
+/// let text = parser.to_string();
+/// assert!(text.contains(r#"motd = "hello\n[admin]\npw = hunter2""#));
+/// assert!(!text.lines().any(|line| line == "[admin]"));
+/// ```
+impl Display for INIParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = WriteOptions::default();
+        let options = match validate(self, &options) {
+            Ok(()) => options,
+            Err(_) => options.escapes(true),
+        };
+        write_ini(f, self, &self.resolve(&options))
+    }
+}
+
+/// Shifts the existing backups of a file up by one and copies the file itself to
+/// `<file>.bak.1`, dropping the backup that falls past `count`.
+fn rotate_backups(path: &Path, count: usize) -> io::Result<()> {
+    if count == 0 || !path.exists() {
+        return Ok(());
+    }
+    let backup = |n: usize| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".bak.{n}"));
+        PathBuf::from(name)
+    };
+    for n in (1..count).rev() {
+        let from = backup(n);
+        if from.exists() {
+            fs::rename(&from, backup(n + 1))?;
+        }
+    }
+    let newest = backup(1);
+    fs::copy(path, &newest)?;
+    let modified = fs::metadata(path)?.modified()?;
+    File::options()
+        .write(true)
+        .open(&newest)?
+        .set_modified(modified)
+}
+
+/// Rewrites a file in place while holding an exclusive advisory lock on it.
+fn write_locked(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut file = File::options()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    file.lock()?;
+    file.set_len(0)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+/// Counts the temporary files created by this process, so that concurrent saves of the same
+/// path never share one.
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes a file through a synced temporary file in the same directory that is then renamed
+/// over the target. The permissions and, where allowed, the owner of an existing target are
+/// carried over to the new file.
+fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    let count = TEMP_FILES.fetch_add(1, Ordering::Relaxed);
+    temp_name.push(format!(".{}.{count}.tmp", process::id()));
+    let temp_path = dir.join(temp_name);
+
+    let existing = fs::metadata(path).ok();
+    // The name is unique to this call, so a file by that name is never ours to remove.
+    let mut file = File::options()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)?;
+    let result = existing
+        .as_ref()
+        .map_or(Ok(()), |existing| copy_metadata(&file, existing))
+        .and_then(|_| file.write_all(content))
+        .and_then(|_| file.sync_all())
+        .and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+
+    // Persist the rename itself; directories cannot be opened as files on Windows.
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Gives a newly created file the permissions and owner of the file it replaces.
+/// Changing the owner usually requires elevated privileges, so failing to do so is ignored.
+fn copy_metadata(file: &File, existing: &fs::Metadata) -> io::Result<()> {
+    file.set_permissions(existing.permissions())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{fchown, MetadataExt};
+        let current = file.metadata()?;
+        if (current.uid(), current.gid()) != (existing.uid(), existing.gid()) {
+            let _ = fchown(file, Some(existing.uid()), Some(existing.gid()));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every section name, key and value can be written without changing the
+/// structure of the file, e.g. a key containing `=` or a value spanning several lines.
+pub(crate) fn validate(parser: &INIParser, options: &WriteOptions) -> INIParserResult<()> {
+    for (section, properties) in &parser.output {
+        if section != &parser.options.default_section {
+            validate_section(section, parser.options.glob_sections)?;
+        }
+        if let Some(parent) = parser.parents.get(section) {
+            if parent.is_empty() || !is_name(parent) {
+                return Err(InIParseError::SerializationError(format!(
+                    "parent {parent:?} of section {section:?} cannot be written"
+                )));
+            }
+        }
+        for key in properties.keys() {
+            for value in parser.get_all(section, key) {
+                validate_property(section, key, value, options)?;
+            }
+        }
+    }
+    for (section, blocks) in &parser.extra_sections {
+        for (key, value) in blocks.iter().flatten() {
+            validate_property(section, key, value, options)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a section name reads back as the `section_name` of the grammar: words of
+/// ASCII letters, digits and `.`, `_`, `/` or `-`, separated by spaces. The quoted subsection
+/// of a git-style name may hold any character but a line break, and so may the glob patterns
+/// read with `ParserOptions::glob_sections`.
+pub(crate) fn validate_section(section: &str, globs: bool) -> INIParserResult<()> {
+    let writable = match section::split_subsection(section) {
+        _ if globs => !section.contains(['\n', '\r']),
+        Some((name, subsection)) => is_section_name(name) && !subsection.contains(['\n', '\r']),
+        None => is_section_name(section),
+    };
+    if !writable {
+        return Err(InIParseError::SerializationError(format!(
+            "section name {section:?} cannot be written"
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the text between the brackets of a section header, escaping the quoted
+/// subsection of a git-style name like `section::subsection_name`. Glob patterns are
+/// written as they are. Line breaks, which only names rejected by `validate_section` hold,
+/// are escaped so the header stays on one line.
+pub(crate) fn header(section: &str, globs: bool) -> Cow<'_, str> {
+    let name = match section::split_subsection(section) {
+        Some((name, subsection)) if !globs => {
+            Cow::Owned(section::subsection_name(name, &subsection))
+        }
+        _ => Cow::Borrowed(section),
+    };
+    match name.contains(['\n', '\r']) {
+        true => Cow::Owned(name.replace('\n', "\\n").replace('\r', "\\r")),
+        false => name,
+    }
+}
+
+/// Checks that a property can be written as a single `key = value` line.
+pub(crate) fn validate_property(
+    section: &str,
+    key: &str,
+    value: &str,
+    options: &WriteOptions,
+) -> INIParserResult<()> {
+    if key.contains(['\n', '\r']) {
+        return Err(InIParseError::SerializationError(format!(
+            "key {key:?} in section {section:?} cannot be written"
+        )));
+    }
+    if !options.escapes && value.contains(['\n', '\r']) {
+        return Err(InIParseError::SerializationError(format!(
+            "value of {section}.{key} cannot be written on a single line"
+        )));
+    }
+    if value_style(value, options).is_none() {
+        return Err(InIParseError::SerializationError(format!(
+            "value of {section}.{key} cannot be quoted without escapes"
+        )));
+    }
+    Ok(())
+}
+
+/// Writes the sections of a config as INI text. A key with several values is written once
+/// per value, and the repeated blocks of a section follow its first block.
+fn write_ini<W: Write>(out: &mut W, parser: &INIParser, options: &WriteOptions) -> fmt::Result {
+    let mut first = true;
+    let default_section = parser.options.default_section.as_str();
+    if let Some(globals) = parser.output.get(default_section) {
+        write_properties(
+            out,
+            globals,
+            &|key| parser.get_all(default_section, key),
+            parser.options.spaced_keys,
+            options,
+        )?;
+        first = globals.is_empty();
+    }
+    for (section, properties) in &parser.output {
+        if section == default_section {
+            continue;
+        }
+        if !first && options.blank_line_between_sections {
+            out.write_str(options.eol())?;
+        }
+        first = false;
+        let name = header(section, parser.options.glob_sections);
+        match parser.parents.get(section) {
+            Some(parent) => write!(out, "[{name} : {parent}]{}", options.eol())?,
+            None => write!(out, "[{name}]{}", options.eol())?,
+        }
+        write_properties(
+            out,
+            properties,
+            &|key| parser.get_all(section, key),
+            parser.options.spaced_keys,
+            options,
+        )?;
+    }
+    for section in parser.output.keys() {
+        let Some(blocks) = parser.extra_sections.get(section) else {
+            continue;
+        };
+        for block in blocks {
+            if !first && options.blank_line_between_sections {
+                out.write_str(options.eol())?;
+            }
+            first = false;
+            let name = header(section, parser.options.glob_sections);
+            write!(out, "[{name}]{}", options.eol())?;
+            write_properties(
+                out,
+                block,
+                &|key| slice::from_ref(&block[key]),
+                parser.options.spaced_keys,
+                options,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the `key = value` lines of a single section, one for each of the `values` of a key.
+fn write_properties<'a, W: Write>(
+    out: &mut W,
+    properties: &'a Properties,
+    values: &dyn Fn(&'a str) -> &'a [String],
+    spaced: bool,
+    options: &WriteOptions,
+) -> fmt::Result {
+    let width = if options.align_delimiters {
+        properties
+            .keys()
+            .map(|key| quote_key(key, spaced).chars().count())
+            .max()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    for (key, value) in properties {
+        match values(key) {
+            // A list emptied by an empty assignment is written as that assignment.
+            [] => write_property(out, key, value, width, spaced, options)?,
+            values => {
+                for value in values {
+                    write_property(out, key, value, width, spaced, options)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes a single `key = value` line, padding the key to `width` characters.
+fn write_property<W: Write>(
+    out: &mut W,
+    key: &str,
+    value: &str,
+    width: usize,
+    spaced: bool,
+    options: &WriteOptions,
+) -> fmt::Result {
+    write!(out, "{:<width$}", quote_key(key, spaced))?;
+    if options.space_around_delimiter {
+        write!(out, " {} ", options.delimiter)?;
+    } else {
+        out.write_char(options.delimiter)?;
+    }
+    write_value(out, value, options)?;
+    out.write_str(options.eol())
+}
+
+/// How a single value is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueStyle {
+    Bare,
+    /// In double quotes, without escapes.
+    Double,
+    /// In single quotes, for values with `"` or `\` that are read verbatim between them.
+    Single,
+    /// In double quotes with backslash escapes, read back with `ParserOptions::escapes`.
+    Escaped,
+}
+
+/// Chooses how a value is written according to the quote policy. Backslash escapes are only
+/// written with `WriteOptions::escapes` or `WriteOptions::escape_unicode`, since values are
+/// read verbatim otherwise. Returns `None` if such a value needs quotes but holds a line break,
+/// or `'` together with `"` or `\`.
+fn value_style(value: &str, options: &WriteOptions) -> Option<ValueStyle> {
+    let escapes = options.escapes || options.escape_unicode;
+    if (options.escapes && value.contains(['\n', '\r', '\t']))
+        || (options.escape_unicode && !value.is_ascii())
+    {
+        return Some(ValueStyle::Escaped);
+    }
+    let quoted = match options.quote {
+        QuotePolicy::Never => false,
+        QuotePolicy::WhenNeeded => needs_quotes(value),
+        QuotePolicy::Always => true,
+    };
+    if !quoted {
+        Some(ValueStyle::Bare)
+    } else if escapes {
+        Some(ValueStyle::Escaped)
+    } else if value.contains(['\n', '\r']) {
+        None
+    } else if !value.contains(['"', '\\']) {
+        Some(ValueStyle::Double)
+    } else if !value.contains('\'') {
+        Some(ValueStyle::Single)
+    } else {
+        None
+    }
+}
+
+/// Writes a single value, quoting it according to the policy.
+fn write_value<W: Write>(out: &mut W, value: &str, options: &WriteOptions) -> fmt::Result {
+    match value_style(value, options) {
+        Some(ValueStyle::Escaped) => {}
+        Some(ValueStyle::Double) => return write!(out, "\"{value}\""),
+        Some(ValueStyle::Single) => return write!(out, "'{value}'"),
+        // Values that cannot be quoted are rejected by `validate` before writing.
+        Some(ValueStyle::Bare) | None => return out.write_str(value),
+    }
+    out.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '\n' if options.escapes => out.write_str("\\n")?,
+            '\r' if options.escapes => out.write_str("\\r")?,
+            '\t' if options.escapes => out.write_str("\\t")?,
+            c if options.escape_unicode && !c.is_ascii() => match u16::try_from(u32::from(c)) {
+                Ok(unit) => write!(out, "\\u{unit:04x}")?,
+                Err(_) => write!(out, "\\x{{{:x}}}", u32::from(c))?,
+            },
+            '"' | '\\' => {
+                out.write_char('\\')?;
+                out.write_char(c)?;
+            }
+            _ => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Wraps a key in double quotes unless it only uses the characters of a bare key, or is a
+/// php-style array key made of those characters. With `spaced`, single spaces between words
+/// are allowed as well.
+pub(crate) fn quote_key(key: &str, spaced: bool) -> Cow<'_, str> {
+    let bare = match key.strip_suffix(']').and_then(|key| key.split_once('[')) {
+        Some((name, index)) => {
+            !name.is_empty() && is_name(name) && is_name(&index.replace('@', ""))
+        }
+        None if spaced => key.split(' ').all(|word| !word.is_empty() && is_name(word)),
+        None => !key.is_empty() && is_name(key),
+    };
+    if bare {
+        return Cow::Borrowed(key);
+    }
+    let mut quoted = String::with_capacity(key.len() + 2);
+    quoted.push('"');
+    for c in key.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            // Only keys rejected by `validate_property` hold line breaks.
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+/// Returns `true` if a name only holds the characters of the grammar's `char` rule.
+fn is_name(name: &str) -> bool {
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '/' | '-'))
+}
+
+/// Returns `true` if a name is one or more words of name characters separated by spaces.
+fn is_section_name(name: &str) -> bool {
+    !name.starts_with(' ')
+        && !name.ends_with(' ')
+        && name.split(' ').any(|word| !word.is_empty())
+        && name.split(' ').all(is_name)
+}
+
+/// Returns `true` if a value would not be read back unchanged without quotes. Backslashes are
+/// read verbatim in bare values, except at the end where they could continue the line.
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty()
+        || value.trim() != value
+        || value.contains([';', '#', '"', '\''])
+        || value.ends_with('\\')
+}
+
+/// Adapts an `io::Write` to `fmt::Write`, encoding the text on the way and keeping the
+/// underlying error that `fmt::Error` cannot carry.
+struct IoAdapter<'a, W: io::Write> {
+    inner: &'a mut W,
+    encoding: Encoding,
+    error: Option<InIParseError>,
+}
+
+impl<'a, W: io::Write> IoAdapter<'a, W> {
+    fn new(inner: &'a mut W, encoding: Encoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            error: None,
+        }
+    }
+
+    /// Writes the byte order mark of the encoding, if it has one.
+    fn write_bom(&mut self) -> INIParserResult<()> {
+        self.inner
+            .write_all(self.encoding.bom())
+            .map_err(|err| InIParseError::FileWriteError(err.to_string()))
+    }
+
+    /// Turns the result of a formatting call into an `INIParserResult`.
+    fn finish(self, result: fmt::Result) -> INIParserResult<()> {
+        match (result, self.error) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(err)) => Err(err),
+            (Err(_), None) => Err(InIParseError::SerializationError(
+                "formatting failed".to_string(),
+            )),
+        }
+    }
+}
+
+impl<W: io::Write> Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let result = match self.encoding {
+            Encoding::Utf8 => self
+                .inner
+                .write_all(s.as_bytes())
+                .map_err(|err| InIParseError::FileWriteError(err.to_string())),
+            encoding => {
+                let mut bytes = Vec::with_capacity(s.len());
+                encoding.encode_into(s, &mut bytes).and_then(|_| {
+                    self.inner
+                        .write_all(&bytes)
+                        .map_err(|err| InIParseError::FileWriteError(err.to_string()))
+                })
+            }
+        };
+        result.map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// A writer emitting INI text incrementally to any `io::Write`.
+/// Sections and properties are written as soon as they are added, so large configs can be
+/// generated without building them in memory first. Since keys are not known in advance,
+/// `WriteOptions::align_delimiters` has no effect here.
+///
+/// ```rust
+/// use dotini::IniWriter;
+///
+/// let mut writer = IniWriter::new(Vec::new());
+/// writer.property("version", "2").unwrap();
+/// writer.section("server").unwrap();
+/// writer.property("port", "8080").unwrap();
+/// let text = String::from_utf8(writer.into_inner()).unwrap();
+/// assert_eq!(text, "version = 2\n\n[server]\nport = 8080\n");
+/// ```
+#[derive(Debug)]
+pub struct IniWriter<W: io::Write> {
+    out: W,
+    options: WriteOptions,
+    section: String,
+    written: bool,
+}
+
+impl<W: io::Write> IniWriter<W> {
+    /// Creates a writer using the default `WriteOptions`.
+    pub fn new(out: W) -> Self {
+        Self::with_options(out, WriteOptions::default())
+    }
+
+    /// Creates a writer using the given options.
+    pub fn with_options(out: W, options: WriteOptions) -> Self {
+        Self {
+            out,
+            options,
+            section: DEFAULT_SECTION.to_string(),
+            written: false,
+        }
+    }
+
+    /**
+     * Starts a new section by writing its header.
+     * Properties written afterwards belong to this section.
+     *
+     * # Arguments
+     * * `name` - The name of the section.
+     *
+     * # Returns
+     * Returns `InIParseError::SerializationError` if the name cannot be written as a section
+     * header, or `InIParseError::FileWriteError` if writing fails.
+     */
+    pub fn section(&mut self, name: &str) -> INIParserResult<()> {
+        validate_section(name, false)?;
+        let blank_line = self.written && self.options.blank_line_between_sections;
+        let mut adapter = IoAdapter::new(&mut self.out, self.options.encoding);
+        if !self.written {
+            adapter.write_bom()?;
+        }
+        let eol = self.options.eol();
+        let result = if blank_line {
+            adapter.write_str(eol)
+        } else {
+            Ok(())
+        };
+        let header = header(name, false);
+        let result = result.and_then(|_| write!(adapter, "[{header}]{eol}"));
+        adapter.finish(result)?;
+        self.section = name.to_string();
+        self.written = true;
+        Ok(())
+    }
+
+    /**
+     * Writes a property into the current section.
+     * Properties written before the first section end up in the `DEFAULT_SECTION`.
+     *
+     * # Arguments
+     * * `key` - The name of the property.
+     * * `value` - The value of the property.
+     *
+     * # Returns
+     * Returns `InIParseError::SerializationError` if the property cannot be written on a single
+     * line, or `InIParseError::FileWriteError` if writing fails.
+     */
+    pub fn property(&mut self, key: &str, value: &str) -> INIParserResult<()> {
+        validate_property(&self.section, key, value, &self.options)?;
+        let mut adapter = IoAdapter::new(&mut self.out, self.options.encoding);
+        if !self.written {
+            adapter.write_bom()?;
+        }
+        let result = write_property(&mut adapter, key, value, 0, false, &self.options);
+        adapter.finish(result)?;
+        self.written = true;
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> INIParserResult<()> {
+        self.out
+            .flush()
+            .map_err(|err| InIParseError::FileWriteError(err.to_string()))
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
@@ -0,0 +1,110 @@
+use dotini::{INIParser, InIParseError, WriteOptions};
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+fn config(port: usize) -> INIParser {
+    INIParser::from_string(&format!(
+        "[server]\nport = {port}\nname = {}\n",
+        "x".repeat(port)
+    ))
+    .unwrap()
+}
+
+fn atomic() -> WriteOptions {
+    WriteOptions::new().atomic(true)
+}
+
+/// Returns the names of the files in a directory, sorted.
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn replaces_the_file_without_leaving_temporary_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    let path = path.to_str().unwrap();
+    config(1)
+        .write_to_file_with_options(path, &atomic())
+        .unwrap();
+    config(2)
+        .write_to_file_with_options(path, &atomic())
+        .unwrap();
+
+    assert_eq!(INIParser::from_file(path).unwrap(), config(2));
+    assert_eq!(file_names(dir.path()), ["app.ini"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_the_permissions_of_the_replaced_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    fs::write(&path, "[server]\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+    config(1)
+        .write_to_file_with_options(path.to_str().unwrap(), &atomic())
+        .unwrap();
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+}
+
+#[test]
+fn a_failed_rename_keeps_the_target_and_removes_the_temporary_file() {
+    let dir = tempfile::tempdir().unwrap();
+    // A non-empty directory cannot be replaced by a file.
+    let path = dir.path().join("app.ini");
+    fs::create_dir(&path).unwrap();
+    fs::write(path.join("keep"), "").unwrap();
+
+    let err = config(1)
+        .write_to_file_with_options(path.to_str().unwrap(), &atomic())
+        .unwrap_err();
+    assert!(matches!(err, InIParseError::FileWriteError(_)));
+    assert!(path.join("keep").exists());
+    assert_eq!(file_names(dir.path()), ["app.ini"]);
+}
+
+#[test]
+fn a_missing_directory_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing").join("app.ini");
+
+    let err = config(1)
+        .write_to_file_with_options(path.to_str().unwrap(), &atomic())
+        .unwrap_err();
+    assert!(matches!(err, InIParseError::FileWriteError(_)));
+    assert!(!dir.path().join("missing").exists());
+}
+
+#[test]
+fn concurrent_saves_of_one_path_never_mix_their_content() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    let path = path.to_str().unwrap();
+
+    thread::scope(|scope| {
+        for writer in 1..=8 {
+            scope.spawn(move || {
+                for round in 0..20 {
+                    let parser = config(writer * 100 + round);
+                    parser.write_to_file_with_options(path, &atomic()).unwrap();
+                    // Whatever save landed last, the file holds all of it.
+                    let read = INIParser::from_file(path).unwrap();
+                    let port: usize = read.get_as("server", "port").unwrap();
+                    assert_eq!(read, config(port));
+                }
+            });
+        }
+    });
+    assert_eq!(file_names(dir.path()), ["app.ini"]);
+}
@@ -0,0 +1,112 @@
+use dotini::{INIParser, InIParseError, WriteOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+fn config(version: usize) -> INIParser {
+    INIParser::from_string(&format!("[app]\nversion = {version}\n")).unwrap()
+}
+
+fn backup(path: &Path, n: usize) -> PathBuf {
+    PathBuf::from(format!("{}.bak.{n}", path.display()))
+}
+
+fn version(path: &Path) -> usize {
+    INIParser::from_file(path.to_str().unwrap())
+        .unwrap()
+        .get_as("app", "version")
+        .unwrap()
+}
+
+#[test]
+fn the_first_save_makes_no_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    config(1)
+        .write_to_file_with_options(path.to_str().unwrap(), &WriteOptions::new().backups(3))
+        .unwrap();
+
+    assert_eq!(version(&path), 1);
+    assert!(!backup(&path, 1).exists());
+}
+
+#[test]
+fn keeps_the_most_recent_versions_and_prunes_the_rest() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    let options = WriteOptions::new().backups(3);
+    for n in 1..=6 {
+        config(n)
+            .write_to_file_with_options(path.to_str().unwrap(), &options)
+            .unwrap();
+    }
+
+    assert_eq!(version(&path), 6);
+    assert_eq!(version(&backup(&path, 1)), 5);
+    assert_eq!(version(&backup(&path, 2)), 4);
+    assert_eq!(version(&backup(&path, 3)), 3);
+    assert!(!backup(&path, 4).exists());
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 4);
+}
+
+#[test]
+fn backups_work_together_with_atomic_saves() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    let options = WriteOptions::new().backups(2).atomic(true);
+    for n in 1..=3 {
+        config(n)
+            .write_to_file_with_options(path.to_str().unwrap(), &options)
+            .unwrap();
+    }
+
+    assert_eq!(version(&path), 3);
+    assert_eq!(version(&backup(&path, 1)), 2);
+    assert_eq!(version(&backup(&path, 2)), 1);
+}
+
+#[test]
+fn a_backup_keeps_the_modification_time_of_its_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    config(1).write_to_file(path.to_str().unwrap()).unwrap();
+    let modified = SystemTime::now() - Duration::from_secs(3600);
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+
+    config(2)
+        .write_to_file_with_options(path.to_str().unwrap(), &WriteOptions::new().backups(1))
+        .unwrap();
+    let backup_modified = fs::metadata(backup(&path, 1)).unwrap().modified().unwrap();
+    assert_eq!(backup_modified, modified);
+}
+
+#[test]
+fn no_backups_are_made_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    config(1).write_to_file(path.to_str().unwrap()).unwrap();
+    config(2).write_to_file(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn a_failed_backup_leaves_the_file_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    config(1).write_to_file(path.to_str().unwrap()).unwrap();
+    // The newest backup cannot be written over a directory.
+    fs::create_dir(backup(&path, 1)).unwrap();
+    fs::write(backup(&path, 1).join("keep"), "").unwrap();
+
+    let err = config(2)
+        .write_to_file_with_options(path.to_str().unwrap(), &WriteOptions::new().backups(1))
+        .unwrap_err();
+    assert!(matches!(err, InIParseError::FileWriteError(_)));
+    assert_eq!(version(&path), 1);
+}
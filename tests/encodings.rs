@@ -0,0 +1,74 @@
+use dotini::{INIParser, InIParseError};
+use std::fs;
+
+const TEXT: &str = "[Setup]\r\nName=Caf\u{e9} \u{1f600}\r\n";
+
+fn utf16(bom: &[u8], unit: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    let mut bytes = bom.to_vec();
+    bytes.extend(TEXT.encode_utf16().flat_map(unit));
+    bytes
+}
+
+/// Reads a file holding the given bytes, returning the error it fails with.
+fn read_error(bytes: &[u8]) -> Option<InIParseError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("setup.ini");
+    fs::write(&path, bytes).unwrap();
+    INIParser::from_file(path.to_str().unwrap()).err()
+}
+
+fn name(bytes: &[u8]) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("setup.ini");
+    fs::write(&path, bytes).unwrap();
+    let parser = INIParser::from_file(path.to_str().unwrap()).unwrap();
+    parser.get("Setup", "Name").unwrap().to_string()
+}
+
+#[test]
+fn reads_utf16_with_a_byte_order_mark() {
+    assert_eq!(
+        name(&utf16(&[0xff, 0xfe], u16::to_le_bytes)),
+        "Caf\u{e9} \u{1f600}"
+    );
+    assert_eq!(
+        name(&utf16(&[0xfe, 0xff], u16::to_be_bytes)),
+        "Caf\u{e9} \u{1f600}"
+    );
+}
+
+#[test]
+fn detects_utf16_without_a_byte_order_mark() {
+    assert_eq!(name(&utf16(&[], u16::to_le_bytes)), "Caf\u{e9} \u{1f600}");
+    assert_eq!(name(&utf16(&[], u16::to_be_bytes)), "Caf\u{e9} \u{1f600}");
+}
+
+#[test]
+fn reads_utf8_with_and_without_a_byte_order_mark() {
+    assert_eq!(name(TEXT.as_bytes()), "Caf\u{e9} \u{1f600}");
+    let mut bytes = vec![0xef, 0xbb, 0xbf];
+    bytes.extend(TEXT.as_bytes());
+    assert_eq!(name(&bytes), "Caf\u{e9} \u{1f600}");
+}
+
+#[test]
+fn rejects_truncated_utf16() {
+    let mut bytes = utf16(&[0xfe, 0xff], u16::to_be_bytes);
+    bytes.pop();
+    let err = read_error(&bytes).unwrap();
+    assert!(matches!(err, InIParseError::FileReadError(message) if message.contains("odd")));
+}
+
+#[test]
+fn rejects_unpaired_utf16_surrogates() {
+    let mut bytes = utf16(&[0xfe, 0xff], u16::to_be_bytes);
+    bytes.extend(0xd800u16.to_be_bytes());
+    let err = read_error(&bytes).unwrap();
+    assert!(matches!(err, InIParseError::FileReadError(_)));
+}
+
+#[test]
+fn rejects_invalid_utf8() {
+    let err = read_error(b"[Setup]\nName=\xff\xfe\n").unwrap();
+    assert!(matches!(err, InIParseError::FileReadError(_)));
+}
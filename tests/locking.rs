@@ -0,0 +1,114 @@
+use dotini::{INIParser, InIParseError};
+use std::fs::{self, File};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+fn config(port: usize) -> INIParser {
+    INIParser::from_string(&format!("[server]\nport = {port}\n")).unwrap()
+}
+
+/// How long a blocked call is given to (wrongly) complete before the lock is released.
+const WAIT: Duration = Duration::from_millis(200);
+
+#[test]
+fn round_trips_through_locked_reads_and_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    let path = path.to_str().unwrap();
+    config(8080).write_to_file_locked(path).unwrap();
+    config(80).write_to_file_locked(path).unwrap();
+
+    // The shorter rewrite leaves nothing of the longer one behind.
+    assert_eq!(fs::read_to_string(path).unwrap(), "[server]\nport = 80\n");
+    assert_eq!(INIParser::from_file_locked(path).unwrap(), config(80));
+}
+
+#[test]
+fn a_locked_write_waits_for_the_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    let path = path.to_str().unwrap().to_string();
+    config(1).write_to_file(&path).unwrap();
+
+    let held = File::open(&path).unwrap();
+    held.lock_shared().unwrap();
+    let (done, finished) = mpsc::channel();
+    let writer = {
+        let path = path.clone();
+        thread::spawn(move || {
+            config(2).write_to_file_locked(&path).unwrap();
+            done.send(()).unwrap();
+        })
+    };
+
+    assert!(finished.recv_timeout(WAIT).is_err());
+    assert_eq!(INIParser::from_file(&path).unwrap(), config(1));
+    held.unlock().unwrap();
+    writer.join().unwrap();
+    assert_eq!(INIParser::from_file(&path).unwrap(), config(2));
+}
+
+#[test]
+fn a_locked_read_waits_for_a_writer() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    let path = path.to_str().unwrap().to_string();
+    config(1).write_to_file(&path).unwrap();
+
+    let held = File::options().write(true).open(&path).unwrap();
+    held.lock().unwrap();
+    let (done, finished) = mpsc::channel();
+    let reader = {
+        let path = path.clone();
+        thread::spawn(move || {
+            let parser = INIParser::from_file_locked(&path).unwrap();
+            done.send(()).unwrap();
+            parser
+        })
+    };
+
+    assert!(finished.recv_timeout(WAIT).is_err());
+    // The write completes under the lock before the reader gets to see the file.
+    fs::write(&path, "[server]\nport = 2\n").unwrap();
+    drop(held);
+    assert_eq!(reader.join().unwrap(), config(2));
+}
+
+#[test]
+fn concurrent_locked_writers_never_mix_their_content() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.ini");
+    let path = path.to_str().unwrap();
+
+    thread::scope(|scope| {
+        for writer in 1..=8 {
+            scope.spawn(move || {
+                for round in 0..20 {
+                    // Alternating lengths make a torn or untruncated write visible.
+                    let port = if round % 2 == 0 {
+                        writer
+                    } else {
+                        writer * 10_000
+                    };
+                    config(port).write_to_file_locked(path).unwrap();
+                    let read = INIParser::from_file_locked(path).unwrap();
+                    let port: usize = read.get_as("server", "port").unwrap();
+                    assert_eq!(read, config(port));
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn a_missing_directory_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing").join("app.ini");
+    let path = path.to_str().unwrap();
+
+    let err = config(1).write_to_file_locked(path).unwrap_err();
+    assert!(matches!(err, InIParseError::FileWriteError(_)));
+    let err = INIParser::from_file_locked(path).unwrap_err();
+    assert!(matches!(err, InIParseError::FileReadError(_)));
+}
@@ -0,0 +1,97 @@
+use dotini::{Dialect, INIParser, ParserOptions};
+
+/// Writes a config and reads it back with the options it was parsed with.
+fn round_trip(content: &str, options: ParserOptions) -> (INIParser, String) {
+    let parser = INIParser::from_string_with_options(content, options.clone()).unwrap();
+    let written = parser.to_ini_string().unwrap();
+    let read = INIParser::from_string_with_options(&written, options).unwrap();
+    assert_eq!(read, parser, "{written}");
+    (read, written)
+}
+
+#[test]
+fn git_subsections_with_header_characters() {
+    let content = "[url \"git@github.com:\"]\n\tinsteadOf = gh:\n\
+                   [includeIf \"gitdir:~/work/\"]\n\tpath = ~/work.gitconfig\n";
+    let (parser, written) = round_trip(content, ParserOptions::dialect(Dialect::GitConfig));
+    assert_eq!(
+        parser.get("url \"git@github.com:\"", "insteadOf"),
+        Some("gh:")
+    );
+    assert!(written.contains("[includeIf \"gitdir:~/work/\"]"));
+}
+
+#[test]
+fn git_subsections_with_quotes_and_backslashes() {
+    let content = "[section \"a \\\"quoted\\\" \\\\ name\"]\nkey = value\n";
+    let (parser, _) = round_trip(content, ParserOptions::dialect(Dialect::GitConfig));
+    assert_eq!(
+        parser.sections().collect::<Vec<_>>(),
+        ["section \"a \\\"quoted\\\" \\\\ name\""]
+    );
+}
+
+#[test]
+fn editorconfig_glob_sections() {
+    let content = "root = true\n\n[*.{js,py}]\nindent_style = space\n\n\
+                   [lib/**/[a-z]*.rs]\nindent_size = 4\n";
+    let (parser, written) = round_trip(content, ParserOptions::editorconfig());
+    assert_eq!(parser.get("lib/**/[a-z]*.rs", "indent_size"), Some("4"));
+    assert!(written.contains("[lib/**/[a-z]*.rs]\n"));
+}
+
+#[test]
+fn a_subsection_does_not_allow_header_characters_in_its_section() {
+    let mut parser = INIParser::from_string("").unwrap();
+    parser.set("a:b \"sub\"", "k", "v");
+    assert!(parser.to_ini_string().is_err());
+}
+
+#[test]
+fn section_names_outside_the_grammar_are_rejected() {
+    for name in ["a#b", "a;b", "a b ", " a", "a\tb", "a=b", "é", ""] {
+        let mut parser = INIParser::from_string("").unwrap();
+        parser.set(name, "k", "v");
+        assert!(parser.to_ini_string().is_err(), "{name:?}");
+    }
+    let mut parser = INIParser::from_string("").unwrap();
+    parser.set("Desktop  Entry", "k", "v");
+    round_trip(&parser.to_ini_string().unwrap(), ParserOptions::new());
+}
+
+/// Every name made of up to three of these characters is either rejected by the writer or
+/// read back unchanged, as a section, a key and a value.
+#[test]
+fn written_configs_read_back_unchanged() {
+    let chars = [
+        'a', '1', '.', '-', ' ', '\t', '#', ';', '[', ']', ':', '=', '"', '\'', '\\', '!', 'é',
+    ];
+    let mut names = vec![String::new()];
+    for length in 1..=3 {
+        let shorter: Vec<_> = names
+            .iter()
+            .filter(|name| name.chars().count() == length - 1)
+            .cloned()
+            .collect();
+        for name in shorter {
+            names.extend(chars.iter().map(|c| format!("{name}{c}")));
+        }
+    }
+    let mut written = [0, 0];
+    for name in &names {
+        let mut section = INIParser::from_string("").unwrap();
+        section.set(name, "k", "v");
+        let mut property = INIParser::from_string("").unwrap();
+        property.set("s", name.clone(), name.clone());
+        for (kind, parser) in [section, property].into_iter().enumerate() {
+            let Ok(text) = parser.to_ini_string() else {
+                continue;
+            };
+            let read = INIParser::from_string(&text)
+                .unwrap_or_else(|err| panic!("{name:?} was written as {text:?}: {err}"));
+            assert_eq!(read, parser, "{name:?} was written as {text:?}");
+            written[kind] += 1;
+        }
+    }
+    assert!(written.iter().all(|&count| count > 0), "{written:?}");
+}
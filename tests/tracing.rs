@@ -0,0 +1,88 @@
+#![cfg(feature = "tracing")]
+
+use dotini::{INIParser, ParserOptions};
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Records the names of the spans opened and the messages of the events emitted.
+#[derive(Clone, Default)]
+struct Recorder {
+    next_id: Arc<AtomicU64>,
+    spans: Arc<Mutex<Vec<String>>>,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.spans
+            .lock()
+            .unwrap()
+            .push(span.metadata().name().to_string());
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        struct Message<'a>(&'a mut String);
+        impl tracing::field::Visit for Message<'_> {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    *self.0 = format!("{value:?}");
+                }
+            }
+        }
+        let mut message = String::new();
+        event.record(&mut Message(&mut message));
+        self.events.lock().unwrap().push(message);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn reading_a_file_with_includes_emits_spans_and_events() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("app.ini"),
+        "[app]\nname = demo\n!include db.ini\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("db.ini"), "[db]\nhost = localhost\n").unwrap();
+    let path = dir.path().join("app.ini");
+
+    let recorder = Recorder::default();
+    let parser = tracing::subscriber::with_default(recorder.clone(), || {
+        let options = ParserOptions::new().includes(true);
+        let mut parser =
+            INIParser::from_file_with_options(path.to_str().unwrap(), options).unwrap();
+        parser.merge(INIParser::from_string("[cache]\nttl = 60\n").unwrap());
+        parser
+    });
+    assert_eq!(parser.get("db", "host"), Some("localhost"));
+
+    let spans = recorder.spans.lock().unwrap();
+    for name in ["from_file_with_options", "read_all", "include", "merge"] {
+        assert!(
+            spans.iter().any(|span| span == name),
+            "no {name} span in {spans:?}"
+        );
+    }
+    let events = recorder.events.lock().unwrap();
+    assert!(
+        events.iter().any(|event| event == "read file"),
+        "{events:?}"
+    );
+    assert!(events.iter().any(|event| event == "parsed"), "{events:?}");
+}